@@ -0,0 +1,24 @@
+use std::process::Command;
+
+/// Exposes the commit being built as `GIT_HASH`, unless it is already set,
+/// for instance when building from a Docker context without `.git`
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+
+    if std::env::var_os("GIT_HASH").is_some() {
+        return;
+    }
+
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(hash) = hash {
+        println!("cargo:rustc-env=GIT_HASH={}", hash.trim());
+    }
+}
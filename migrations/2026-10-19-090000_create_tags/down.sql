@@ -0,0 +1,2 @@
+DROP TABLE homework_tags;
+DROP TABLE tags;
@@ -0,0 +1,17 @@
+CREATE TABLE tags (
+  id SERIAL PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  name VARCHAR NOT NULL UNIQUE,
+  hex_color VARCHAR
+);
+
+SELECT diesel_manage_updated_at('tags');
+
+CREATE TABLE homework_tags (
+  homework_id INTEGER NOT NULL REFERENCES homeworks(id) ON DELETE CASCADE,
+  tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+  PRIMARY KEY (homework_id, tag_id)
+);
+
+CREATE INDEX homework_tags_tag_id_idx ON homework_tags (tag_id);
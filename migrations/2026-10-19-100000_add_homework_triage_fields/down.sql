@@ -0,0 +1,6 @@
+ALTER TABLE homeworks
+DROP COLUMN priority,
+DROP COLUMN estimated_minutes,
+DROP COLUMN difficulty;
+
+DROP TYPE priority;
@@ -0,0 +1,6 @@
+CREATE TYPE priority AS ENUM ('low', 'normal', 'high', 'urgent');
+
+ALTER TABLE homeworks
+ADD priority priority NOT NULL DEFAULT 'normal',
+ADD estimated_minutes INTEGER CHECK (estimated_minutes > 0),
+ADD difficulty SMALLINT CHECK (difficulty BETWEEN 1 AND 5);
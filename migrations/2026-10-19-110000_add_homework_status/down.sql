@@ -0,0 +1,13 @@
+DROP TRIGGER record_status_change ON homeworks;
+DROP FUNCTION record_homework_status_change();
+
+DROP TABLE homework_status_changes;
+
+ALTER TABLE homeworks DROP COLUMN done;
+ALTER TABLE homeworks ADD done BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE homeworks SET done = status IN ('done', 'submitted', 'graded', 'returned');
+
+ALTER TABLE homeworks DROP COLUMN status;
+
+DROP TYPE homework_status;
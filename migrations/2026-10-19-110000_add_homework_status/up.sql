@@ -0,0 +1,40 @@
+CREATE TYPE homework_status AS ENUM ('todo', 'started', 'done', 'submitted', 'graded', 'returned');
+
+ALTER TABLE homeworks ADD status homework_status NOT NULL DEFAULT 'todo';
+
+UPDATE homeworks SET status = 'done' WHERE done;
+
+-- `done` is kept for compatibility, derived from the status
+ALTER TABLE homeworks DROP COLUMN done;
+ALTER TABLE homeworks ADD done BOOLEAN
+  NOT NULL
+  GENERATED ALWAYS AS (status IN ('done', 'submitted', 'graded', 'returned')) STORED;
+
+CREATE TABLE homework_status_changes (
+  id SERIAL PRIMARY KEY,
+  homework_id INTEGER NOT NULL REFERENCES homeworks(id) ON DELETE CASCADE,
+  from_status homework_status,
+  to_status homework_status NOT NULL,
+  changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX homework_status_changes_homework_id_idx ON homework_status_changes (homework_id);
+
+INSERT INTO homework_status_changes (homework_id, to_status, changed_at)
+SELECT id, status, created_at FROM homeworks;
+
+CREATE FUNCTION record_homework_status_change() RETURNS trigger AS $$
+BEGIN
+    IF (TG_OP = 'INSERT') THEN
+        INSERT INTO homework_status_changes (homework_id, to_status)
+        VALUES (NEW.id, NEW.status);
+    ELSIF (NEW.status IS DISTINCT FROM OLD.status) THEN
+        INSERT INTO homework_status_changes (homework_id, from_status, to_status)
+        VALUES (NEW.id, OLD.status, NEW.status);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_status_change AFTER INSERT OR UPDATE OF status ON homeworks
+    FOR EACH ROW EXECUTE PROCEDURE record_homework_status_change();
@@ -0,0 +1,18 @@
+CREATE TABLE time_entries (
+  id SERIAL PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  homework_id INTEGER NOT NULL REFERENCES homeworks(id) ON DELETE CASCADE,
+  started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  ended_at TIMESTAMPTZ,
+  note VARCHAR NOT NULL DEFAULT '',
+  CHECK (ended_at IS NULL OR ended_at > started_at)
+);
+
+SELECT diesel_manage_updated_at('time_entries');
+
+CREATE INDEX time_entries_homework_id_idx ON time_entries (homework_id);
+
+-- Only one timer can be running at a time
+CREATE UNIQUE INDEX time_entries_running_idx ON time_entries ((ended_at IS NULL))
+  WHERE ended_at IS NULL;
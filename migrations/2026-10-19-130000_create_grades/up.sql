@@ -0,0 +1,16 @@
+CREATE TABLE grades (
+  id SERIAL PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  subject_id INTEGER NOT NULL REFERENCES subjects(id) ON DELETE CASCADE,
+  homework_id INTEGER REFERENCES homeworks(id) ON DELETE SET NULL,
+  title VARCHAR NOT NULL DEFAULT '',
+  score DOUBLE PRECISION NOT NULL CHECK (score >= 0),
+  max_score DOUBLE PRECISION NOT NULL DEFAULT 20 CHECK (max_score > 0),
+  coefficient DOUBLE PRECISION NOT NULL DEFAULT 1 CHECK (coefficient > 0),
+  graded_on DATE NOT NULL DEFAULT CURRENT_DATE
+);
+
+SELECT diesel_manage_updated_at('grades');
+
+CREATE INDEX grades_subject_id_idx ON grades (subject_id);
@@ -0,0 +1,4 @@
+ALTER TABLE homeworks
+DROP COLUMN exam_id;
+
+DROP TABLE exams;
@@ -0,0 +1,17 @@
+CREATE TABLE exams (
+  id SERIAL PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  subject_id INTEGER REFERENCES subjects(id) ON DELETE SET NULL,
+  title VARCHAR NOT NULL,
+  starts_at TIMESTAMPTZ NOT NULL,
+  duration_minutes INTEGER NOT NULL DEFAULT 60 CHECK (duration_minutes > 0),
+  room VARCHAR,
+  chapters VARCHAR[] NOT NULL DEFAULT '{}'
+);
+
+SELECT diesel_manage_updated_at('exams');
+
+-- Revision homeworks generated for an exam
+ALTER TABLE homeworks
+ADD exam_id INTEGER REFERENCES exams(id) ON DELETE SET NULL;
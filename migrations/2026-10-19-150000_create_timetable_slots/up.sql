@@ -0,0 +1,19 @@
+CREATE TYPE week_rotation AS ENUM ('every', 'a', 'b');
+
+CREATE TABLE timetable_slots (
+  id SERIAL PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  subject_id INTEGER NOT NULL REFERENCES subjects(id) ON DELETE CASCADE,
+  -- ISO weekday, 1 is Monday
+  weekday SMALLINT NOT NULL CHECK (weekday BETWEEN 1 AND 7),
+  starts_at TIME NOT NULL,
+  ends_at TIME NOT NULL,
+  room VARCHAR,
+  rotation week_rotation NOT NULL DEFAULT 'every',
+  CHECK (ends_at > starts_at)
+);
+
+SELECT diesel_manage_updated_at('timetable_slots');
+
+CREATE INDEX timetable_slots_subject_id_idx ON timetable_slots (subject_id);
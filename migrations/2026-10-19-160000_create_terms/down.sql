@@ -0,0 +1,5 @@
+ALTER TABLE homeworks DROP COLUMN archived_at;
+ALTER TABLE subjects DROP COLUMN archived_at;
+
+DROP TABLE holidays;
+DROP TABLE terms;
@@ -0,0 +1,26 @@
+CREATE TABLE terms (
+  id SERIAL PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  name VARCHAR NOT NULL,
+  starts_on DATE NOT NULL,
+  ends_on DATE NOT NULL,
+  CHECK (ends_on >= starts_on)
+);
+
+SELECT diesel_manage_updated_at('terms');
+
+CREATE TABLE holidays (
+  id SERIAL PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  name VARCHAR NOT NULL,
+  starts_on DATE NOT NULL,
+  ends_on DATE NOT NULL,
+  CHECK (ends_on >= starts_on)
+);
+
+SELECT diesel_manage_updated_at('holidays');
+
+ALTER TABLE subjects ADD COLUMN archived_at TIMESTAMPTZ;
+ALTER TABLE homeworks ADD COLUMN archived_at TIMESTAMPTZ;
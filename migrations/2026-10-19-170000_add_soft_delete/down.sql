@@ -0,0 +1,8 @@
+UPDATE homeworks
+SET subject_id = trashed_subject_id
+WHERE trashed_subject_id IS NOT NULL;
+
+ALTER TABLE homeworks DROP COLUMN trashed_subject_id;
+ALTER TABLE homeworks DROP COLUMN deleted_at;
+
+ALTER TABLE subjects DROP COLUMN deleted_at;
@@ -0,0 +1,7 @@
+ALTER TABLE subjects ADD COLUMN deleted_at TIMESTAMPTZ;
+
+ALTER TABLE homeworks ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- Subject of the homework while that subject is in the trash
+ALTER TABLE homeworks
+ADD trashed_subject_id INTEGER REFERENCES subjects(id) ON DELETE SET NULL;
@@ -0,0 +1,7 @@
+DROP TRIGGER record_history ON subjects;
+DROP TRIGGER record_history ON homeworks;
+DROP FUNCTION record_history();
+
+DROP TABLE history_entries;
+DROP FUNCTION forbid_history_changes();
+DROP TYPE history_action;
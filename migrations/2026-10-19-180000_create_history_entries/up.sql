@@ -0,0 +1,82 @@
+CREATE TYPE history_action AS ENUM ('create', 'update', 'delete', 'restore', 'purge');
+
+CREATE TABLE history_entries (
+  id BIGSERIAL PRIMARY KEY,
+  table_name VARCHAR NOT NULL,
+  record_id INTEGER NOT NULL,
+  action history_action NOT NULL,
+  -- Value of the `homeworks.actor` setting of the transaction
+  actor VARCHAR,
+  changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  -- `{"field": {"before": ..., "after": ...}}` for each changed field
+  changes JSONB NOT NULL
+);
+
+CREATE INDEX history_entries_record_idx ON history_entries (table_name, record_id);
+
+CREATE FUNCTION forbid_history_changes() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'history entries are append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER append_only BEFORE UPDATE OR DELETE ON history_entries
+    FOR EACH ROW EXECUTE PROCEDURE forbid_history_changes();
+
+CREATE FUNCTION record_history() RETURNS trigger AS $$
+DECLARE
+    old_row JSONB := '{}';
+    new_row JSONB := '{}';
+    changes JSONB := '{}';
+    field TEXT;
+    entry_action history_action;
+BEGIN
+    IF (TG_OP <> 'INSERT') THEN
+        old_row := to_jsonb(OLD) - 'textsearchable_index_col' - 'updated_at';
+    END IF;
+
+    IF (TG_OP <> 'DELETE') THEN
+        new_row := to_jsonb(NEW) - 'textsearchable_index_col' - 'updated_at';
+    END IF;
+
+    FOR field IN SELECT jsonb_object_keys(old_row || new_row) LOOP
+        IF (old_row -> field IS DISTINCT FROM new_row -> field) THEN
+            changes := changes || jsonb_build_object(
+                field,
+                jsonb_build_object('before', old_row -> field, 'after', new_row -> field)
+            );
+        END IF;
+    END LOOP;
+
+    IF (TG_OP = 'INSERT') THEN
+        entry_action := 'create';
+    ELSIF (TG_OP = 'DELETE') THEN
+        entry_action := 'purge';
+    ELSIF (changes = '{}') THEN
+        RETURN NULL;
+    ELSIF (old_row ->> 'deleted_at' IS NULL AND new_row ->> 'deleted_at' IS NOT NULL) THEN
+        entry_action := 'delete';
+    ELSIF (old_row ->> 'deleted_at' IS NOT NULL AND new_row ->> 'deleted_at' IS NULL) THEN
+        entry_action := 'restore';
+    ELSE
+        entry_action := 'update';
+    END IF;
+
+    INSERT INTO history_entries (table_name, record_id, action, actor, changes)
+    VALUES (
+        TG_TABLE_NAME,
+        CAST(COALESCE(new_row ->> 'id', old_row ->> 'id') AS INTEGER),
+        entry_action,
+        NULLIF(current_setting('homeworks.actor', true), ''),
+        changes
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_history AFTER INSERT OR UPDATE OR DELETE ON homeworks
+    FOR EACH ROW EXECUTE PROCEDURE record_history();
+
+CREATE TRIGGER record_history AFTER INSERT OR UPDATE OR DELETE ON subjects
+    FOR EACH ROW EXECUTE PROCEDURE record_history();
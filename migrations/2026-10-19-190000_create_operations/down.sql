@@ -0,0 +1,59 @@
+DROP FUNCTION revert_history_entry(BIGINT);
+
+CREATE OR REPLACE FUNCTION record_history() RETURNS trigger AS $$
+DECLARE
+    old_row JSONB := '{}';
+    new_row JSONB := '{}';
+    changes JSONB := '{}';
+    field TEXT;
+    entry_action history_action;
+BEGIN
+    IF (TG_OP <> 'INSERT') THEN
+        old_row := to_jsonb(OLD) - 'textsearchable_index_col' - 'updated_at';
+    END IF;
+
+    IF (TG_OP <> 'DELETE') THEN
+        new_row := to_jsonb(NEW) - 'textsearchable_index_col' - 'updated_at';
+    END IF;
+
+    FOR field IN SELECT jsonb_object_keys(old_row || new_row) LOOP
+        IF (old_row -> field IS DISTINCT FROM new_row -> field) THEN
+            changes := changes || jsonb_build_object(
+                field,
+                jsonb_build_object('before', old_row -> field, 'after', new_row -> field)
+            );
+        END IF;
+    END LOOP;
+
+    IF (TG_OP = 'INSERT') THEN
+        entry_action := 'create';
+    ELSIF (TG_OP = 'DELETE') THEN
+        entry_action := 'purge';
+    ELSIF (changes = '{}') THEN
+        RETURN NULL;
+    ELSIF (old_row ->> 'deleted_at' IS NULL AND new_row ->> 'deleted_at' IS NOT NULL) THEN
+        entry_action := 'delete';
+    ELSIF (old_row ->> 'deleted_at' IS NOT NULL AND new_row ->> 'deleted_at' IS NULL) THEN
+        entry_action := 'restore';
+    ELSE
+        entry_action := 'update';
+    END IF;
+
+    INSERT INTO history_entries (table_name, record_id, action, actor, changes)
+    VALUES (
+        TG_TABLE_NAME,
+        CAST(COALESCE(new_row ->> 'id', old_row ->> 'id') AS INTEGER),
+        entry_action,
+        NULLIF(current_setting('homeworks.actor', true), ''),
+        changes
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION current_operation_id();
+
+ALTER TABLE history_entries DROP COLUMN operation_id;
+
+DROP TABLE operations;
@@ -0,0 +1,132 @@
+-- Changes made to homeworks and subjects in a single transaction
+CREATE TABLE operations (
+  id SERIAL PRIMARY KEY,
+  performed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  actor VARCHAR,
+  -- Operation this one reverted
+  undo_of INTEGER REFERENCES operations(id),
+  undone_at TIMESTAMPTZ
+);
+
+ALTER TABLE history_entries ADD operation_id INTEGER REFERENCES operations(id);
+
+CREATE INDEX history_entries_operation_id_idx ON history_entries (operation_id);
+
+-- Operation of the current transaction, created on first use
+CREATE FUNCTION current_operation_id() RETURNS INTEGER AS $$
+DECLARE
+    operation_id INTEGER := CAST(NULLIF(current_setting('homeworks.operation_id', true), '') AS INTEGER);
+BEGIN
+    IF (operation_id IS NULL) THEN
+        INSERT INTO operations (actor, undo_of)
+        VALUES (
+            NULLIF(current_setting('homeworks.actor', true), ''),
+            CAST(NULLIF(current_setting('homeworks.undo_of', true), '') AS INTEGER)
+        )
+        RETURNING id INTO operation_id;
+
+        PERFORM set_config('homeworks.operation_id', CAST(operation_id AS TEXT), true);
+    END IF;
+
+    RETURN operation_id;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_history() RETURNS trigger AS $$
+DECLARE
+    old_row JSONB := '{}';
+    new_row JSONB := '{}';
+    changes JSONB := '{}';
+    field TEXT;
+    entry_action history_action;
+BEGIN
+    IF (TG_OP <> 'INSERT') THEN
+        old_row := to_jsonb(OLD) - 'textsearchable_index_col' - 'updated_at';
+    END IF;
+
+    IF (TG_OP <> 'DELETE') THEN
+        new_row := to_jsonb(NEW) - 'textsearchable_index_col' - 'updated_at';
+    END IF;
+
+    FOR field IN SELECT jsonb_object_keys(old_row || new_row) LOOP
+        IF (old_row -> field IS DISTINCT FROM new_row -> field) THEN
+            changes := changes || jsonb_build_object(
+                field,
+                jsonb_build_object('before', old_row -> field, 'after', new_row -> field)
+            );
+        END IF;
+    END LOOP;
+
+    IF (TG_OP = 'INSERT') THEN
+        entry_action := 'create';
+    ELSIF (TG_OP = 'DELETE') THEN
+        entry_action := 'purge';
+    ELSIF (changes = '{}') THEN
+        RETURN NULL;
+    ELSIF (old_row ->> 'deleted_at' IS NULL AND new_row ->> 'deleted_at' IS NOT NULL) THEN
+        entry_action := 'delete';
+    ELSIF (old_row ->> 'deleted_at' IS NOT NULL AND new_row ->> 'deleted_at' IS NULL) THEN
+        entry_action := 'restore';
+    ELSE
+        entry_action := 'update';
+    END IF;
+
+    INSERT INTO history_entries (table_name, record_id, action, actor, changes, operation_id)
+    VALUES (
+        TG_TABLE_NAME,
+        CAST(COALESCE(new_row ->> 'id', old_row ->> 'id') AS INTEGER),
+        entry_action,
+        NULLIF(current_setting('homeworks.actor', true), ''),
+        changes,
+        current_operation_id()
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Puts back the values a history entry changed, records created by the entry
+-- are moved to the trash
+CREATE FUNCTION revert_history_entry(entry_id BIGINT) RETURNS VOID AS $$
+DECLARE
+    entry history_entries;
+    previous_values JSONB;
+    assignments TEXT;
+BEGIN
+    SELECT * INTO entry FROM history_entries WHERE id = entry_id;
+
+    IF (entry.action = 'purge') THEN
+        RAISE EXCEPTION 'purged records cannot be restored';
+    END IF;
+
+    IF (entry.action = 'create') THEN
+        EXECUTE format('UPDATE %I SET deleted_at = NOW() WHERE id = $1', entry.table_name)
+        USING entry.record_id;
+
+        RETURN;
+    END IF;
+
+    SELECT jsonb_object_agg(key, value -> 'before'),
+           string_agg(format('%I = previous.%I', key, key), ', ')
+    INTO previous_values, assignments
+    FROM jsonb_each(entry.changes)
+    WHERE key NOT IN (
+        SELECT column_name
+        FROM information_schema.columns
+        WHERE table_schema = current_schema()
+          AND table_name = entry.table_name
+          AND is_generated = 'ALWAYS'
+    );
+
+    IF (assignments IS NULL) THEN
+        RETURN;
+    END IF;
+
+    EXECUTE format(
+        'UPDATE %1$I SET %2$s FROM jsonb_populate_record(NULL::%1$I, $1) AS previous WHERE %1$I.id = $2',
+        entry.table_name,
+        assignments
+    )
+    USING previous_values, entry.record_id;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1 @@
+DROP TABLE homework_templates;
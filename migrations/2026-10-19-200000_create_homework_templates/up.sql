@@ -0,0 +1,19 @@
+CREATE TABLE homework_templates (
+  id SERIAL PRIMARY KEY,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  name VARCHAR NOT NULL,
+  -- Title and description may contain `{date}` and `{chapter}` placeholders
+  title VARCHAR NOT NULL,
+  description VARCHAR NOT NULL DEFAULT '',
+  subject_id INTEGER REFERENCES subjects(id) ON DELETE SET NULL,
+  priority priority NOT NULL DEFAULT 'normal',
+  estimated_minutes INTEGER CHECK (estimated_minutes > 0),
+  difficulty SMALLINT CHECK (difficulty BETWEEN 1 AND 5),
+  tag_ids INTEGER[] NOT NULL DEFAULT '{}',
+  -- Homeworks are due this many days after the instantiation date
+  due_in_days INTEGER CHECK (due_in_days BETWEEN 0 AND 366),
+  due_time TIME NOT NULL DEFAULT '08:00'
+);
+
+SELECT diesel_manage_updated_at('homework_templates');
@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    errors::{unprocessable_entity, AppResult},
+    models,
+};
+
+/// Rows inserted by a single statement, postgres taking at most 65535 bound
+/// values in one
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Loads every record of the database, from a single snapshot
+pub async fn dump(conn: &mut AsyncPgConnection) -> QueryResult<models::Export> {
+    use crate::schema::*;
+
+    conn.build_transaction()
+        .repeatable_read()
+        .read_only()
+        .run(|conn| {
+            async move {
+                Ok(models::Export {
+                    schema_version: models::EXPORT_SCHEMA_VERSION,
+                    exported_at: chrono::Utc::now(),
+                    subjects: subjects::table
+                        .select(models::Subject::as_select())
+                        .order_by(subjects::id)
+                        .load(conn)
+                        .await?,
+                    tags: tags::table.order_by(tags::id).load(conn).await?,
+                    exams: exams::table.order_by(exams::id).load(conn).await?,
+                    homeworks: homeworks::table
+                        .select(models::HOMEWORK_ALL_COLUMNS)
+                        .order_by(homeworks::id)
+                        .load(conn)
+                        .await?,
+                    homework_tags: homework_tags::table
+                        .order_by((homework_tags::homework_id, homework_tags::tag_id))
+                        .load(conn)
+                        .await?,
+                    homework_status_changes: homework_status_changes::table
+                        .order_by(homework_status_changes::id)
+                        .load(conn)
+                        .await?,
+                    homework_templates: homework_templates::table
+                        .order_by(homework_templates::id)
+                        .load(conn)
+                        .await?,
+                    grades: grades::table.order_by(grades::id).load(conn).await?,
+                    time_entries: time_entries::table
+                        .order_by(time_entries::id)
+                        .load(conn)
+                        .await?,
+                    timetable_slots: timetable_slots::table
+                        .order_by(timetable_slots::id)
+                        .load(conn)
+                        .await?,
+                    terms: terms::table.order_by(terms::id).load(conn).await?,
+                    holidays: holidays::table.order_by(holidays::id).load(conn).await?,
+                })
+            }
+            .scope_boxed()
+        })
+        .await
+}
+
+/// Ids of the records of a dump, mapped to the ids they got once imported
+#[derive(Debug, Default)]
+struct IdMap(HashMap<i32, i32>);
+
+impl IdMap {
+    fn insert(&mut self, old_id: i32, new_id: i32) {
+        self.0.insert(old_id, new_id);
+    }
+
+    /// New id of a record referenced by the dump, which must be part of it
+    fn get(&self, old_id: i32) -> AppResult<i32> {
+        self.0
+            .get(&old_id)
+            .copied()
+            .ok_or_else(unprocessable_entity)
+    }
+
+    fn get_optional(&self, old_id: Option<i32>) -> AppResult<Option<i32>> {
+        old_id.map(|old_id| self.get(old_id)).transpose()
+    }
+}
+
+/// Adds the records of a dump to the database, giving them new ids but
+/// keeping their timestamps
+///
+/// Must run inside a transaction, so that a dump is never partially imported.
+pub async fn restore(
+    conn: &mut AsyncPgConnection,
+    export: models::Export,
+    mode: models::ImportMode,
+) -> AppResult<models::ImportSummary> {
+    use crate::schema::*;
+
+    if export.schema_version != models::EXPORT_SCHEMA_VERSION {
+        return Err(unprocessable_entity());
+    }
+
+    let mut summary = models::ImportSummary::default();
+
+    if mode == models::ImportMode::Replace {
+        // Tags, status changes and time entries go away with their homeworks
+        diesel::delete(homework_templates::table)
+            .execute(conn)
+            .await?;
+        diesel::delete(grades::table).execute(conn).await?;
+        diesel::delete(timetable_slots::table).execute(conn).await?;
+        diesel::delete(homeworks::table).execute(conn).await?;
+        diesel::delete(exams::table).execute(conn).await?;
+        diesel::delete(subjects::table).execute(conn).await?;
+        diesel::delete(tags::table).execute(conn).await?;
+        diesel::delete(terms::table).execute(conn).await?;
+        diesel::delete(holidays::table).execute(conn).await?;
+    }
+
+    let existing_subjects = subjects::table
+        .filter(subjects::deleted_at.is_null())
+        .select((subjects::name, subjects::id))
+        .load::<(String, i32)>(conn)
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let mut subject_ids = IdMap::default();
+
+    for subject in export.subjects {
+        let new_id = match existing_subjects.get(&subject.name) {
+            Some(existing_id) if subject.deleted_at.is_none() => *existing_id,
+            _ => {
+                summary.subjects += 1;
+
+                diesel::insert_into(subjects::table)
+                    .values((
+                        subjects::created_at.eq(subject.created_at),
+                        subjects::updated_at.eq(subject.updated_at),
+                        subjects::name.eq(subject.name),
+                        subjects::hex_color.eq(subject.hex_color),
+                        subjects::archived_at.eq(subject.archived_at),
+                        subjects::deleted_at.eq(subject.deleted_at),
+                    ))
+                    .returning(subjects::id)
+                    .get_result::<i32>(conn)
+                    .await?
+            }
+        };
+
+        subject_ids.insert(subject.id, new_id);
+    }
+
+    let existing_tags = tags::table
+        .select((tags::name, tags::id))
+        .load::<(String, i32)>(conn)
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let mut tag_ids = IdMap::default();
+
+    for tag in export.tags {
+        let new_id = match existing_tags.get(&tag.name) {
+            Some(existing_id) => *existing_id,
+            None => {
+                summary.tags += 1;
+
+                diesel::insert_into(tags::table)
+                    .values((
+                        tags::created_at.eq(tag.created_at),
+                        tags::updated_at.eq(tag.updated_at),
+                        tags::name.eq(tag.name),
+                        tags::hex_color.eq(tag.hex_color),
+                    ))
+                    .returning(tags::id)
+                    .get_result::<i32>(conn)
+                    .await?
+            }
+        };
+
+        tag_ids.insert(tag.id, new_id);
+    }
+
+    let mut exam_ids = IdMap::default();
+
+    for exam in export.exams {
+        let new_id = diesel::insert_into(exams::table)
+            .values((
+                exams::created_at.eq(exam.created_at),
+                exams::updated_at.eq(exam.updated_at),
+                exams::subject_id.eq(subject_ids.get_optional(exam.subject_id)?),
+                exams::title.eq(exam.title),
+                exams::starts_at.eq(exam.starts_at),
+                exams::duration_minutes.eq(exam.duration_minutes),
+                exams::room.eq(exam.room),
+                exams::chapters.eq(exam.chapters),
+            ))
+            .returning(exams::id)
+            .get_result::<i32>(conn)
+            .await?;
+
+        exam_ids.insert(exam.id, new_id);
+        summary.exams += 1;
+    }
+
+    let mut homework_ids = IdMap::default();
+
+    for homework in export.homeworks {
+        let new_id = diesel::insert_into(homeworks::table)
+            .values((
+                homeworks::created_at.eq(homework.created_at),
+                homeworks::updated_at.eq(homework.updated_at),
+                homeworks::due_date.eq(homework.due_date),
+                homeworks::title.eq(homework.title),
+                homeworks::description.eq(homework.description),
+                homeworks::subject_id.eq(subject_ids.get_optional(homework.subject_id)?),
+                homeworks::priority.eq(homework.priority),
+                homeworks::estimated_minutes.eq(homework.estimated_minutes),
+                homeworks::difficulty.eq(homework.difficulty),
+                homeworks::status.eq(homework.status),
+                homeworks::exam_id.eq(exam_ids.get_optional(homework.exam_id)?),
+                homeworks::archived_at.eq(homework.archived_at),
+                homeworks::deleted_at.eq(homework.deleted_at),
+                homeworks::trashed_subject_id
+                    .eq(subject_ids.get_optional(homework.trashed_subject_id)?),
+            ))
+            .returning(homeworks::id)
+            .get_result::<i32>(conn)
+            .await?;
+
+        homework_ids.insert(homework.id, new_id);
+        summary.homeworks += 1;
+    }
+
+    // Inserting the homeworks recorded a status change each, replaced by the
+    // changes of the dump
+    diesel::delete(
+        homework_status_changes::table
+            .filter(homework_status_changes::homework_id.eq_any(homework_ids.0.values())),
+    )
+    .execute(conn)
+    .await?;
+
+    let rows = export
+        .homework_status_changes
+        .into_iter()
+        .map(|change| {
+            Ok((
+                homework_status_changes::homework_id.eq(homework_ids.get(change.homework_id)?),
+                homework_status_changes::from_status.eq(change.from_status),
+                homework_status_changes::to_status.eq(change.to_status),
+                homework_status_changes::changed_at.eq(change.changed_at),
+            ))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        diesel::insert_into(homework_status_changes::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    let rows = export
+        .homework_tags
+        .into_iter()
+        .map(|homework_tag| {
+            Ok(models::HomeworkTag {
+                homework_id: homework_ids.get(homework_tag.homework_id)?,
+                tag_id: tag_ids.get(homework_tag.tag_id)?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        diesel::insert_into(homework_tags::table)
+            .values(chunk)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?;
+    }
+
+    let rows = export
+        .homework_templates
+        .into_iter()
+        .map(|template| {
+            Ok((
+                homework_templates::created_at.eq(template.created_at),
+                homework_templates::updated_at.eq(template.updated_at),
+                homework_templates::name.eq(template.name),
+                homework_templates::title.eq(template.title),
+                homework_templates::description.eq(template.description),
+                homework_templates::subject_id.eq(subject_ids.get_optional(template.subject_id)?),
+                homework_templates::priority.eq(template.priority),
+                homework_templates::estimated_minutes.eq(template.estimated_minutes),
+                homework_templates::difficulty.eq(template.difficulty),
+                // Templates keep the ids of tags that may since have been deleted
+                homework_templates::tag_ids.eq(template
+                    .tag_ids
+                    .into_iter()
+                    .filter_map(|tag_id| tag_ids.get(tag_id).ok())
+                    .collect::<Vec<_>>()),
+                homework_templates::due_in_days.eq(template.due_in_days),
+                homework_templates::due_time.eq(template.due_time),
+            ))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        summary.homework_templates += diesel::insert_into(homework_templates::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    let rows = export
+        .grades
+        .into_iter()
+        .map(|grade| {
+            Ok((
+                grades::created_at.eq(grade.created_at),
+                grades::updated_at.eq(grade.updated_at),
+                grades::subject_id.eq(subject_ids.get(grade.subject_id)?),
+                grades::homework_id.eq(homework_ids.get_optional(grade.homework_id)?),
+                grades::title.eq(grade.title),
+                grades::score.eq(grade.score),
+                grades::max_score.eq(grade.max_score),
+                grades::coefficient.eq(grade.coefficient),
+                grades::graded_on.eq(grade.graded_on),
+            ))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        summary.grades += diesel::insert_into(grades::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    // Only one timer can be running, the ones of the dump are stopped when it
+    // was made if another one already is
+    let mut running = diesel::select(diesel::dsl::exists(
+        time_entries::table.filter(time_entries::ended_at.is_null()),
+    ))
+    .get_result::<bool>(conn)
+    .await?;
+
+    let rows = export
+        .time_entries
+        .into_iter()
+        .map(|entry| {
+            let ended_at = match entry.ended_at {
+                None if running => Some(export.exported_at),
+                None => {
+                    running = true;
+                    None
+                }
+                ended_at => ended_at,
+            };
+
+            Ok((
+                time_entries::created_at.eq(entry.created_at),
+                time_entries::updated_at.eq(entry.updated_at),
+                time_entries::homework_id.eq(homework_ids.get(entry.homework_id)?),
+                time_entries::started_at.eq(entry.started_at),
+                time_entries::ended_at.eq(ended_at),
+                time_entries::note.eq(entry.note),
+            ))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        summary.time_entries += diesel::insert_into(time_entries::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    let rows = export
+        .timetable_slots
+        .into_iter()
+        .map(|slot| {
+            Ok((
+                timetable_slots::created_at.eq(slot.created_at),
+                timetable_slots::updated_at.eq(slot.updated_at),
+                timetable_slots::subject_id.eq(subject_ids.get(slot.subject_id)?),
+                timetable_slots::weekday.eq(slot.weekday),
+                timetable_slots::starts_at.eq(slot.starts_at),
+                timetable_slots::ends_at.eq(slot.ends_at),
+                timetable_slots::room.eq(slot.room),
+                timetable_slots::rotation.eq(slot.rotation),
+            ))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        summary.timetable_slots += diesel::insert_into(timetable_slots::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    let rows = export
+        .terms
+        .into_iter()
+        .map(|term| {
+            (
+                terms::created_at.eq(term.created_at),
+                terms::updated_at.eq(term.updated_at),
+                terms::name.eq(term.name),
+                terms::starts_on.eq(term.starts_on),
+                terms::ends_on.eq(term.ends_on),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        summary.terms += diesel::insert_into(terms::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    let rows = export
+        .holidays
+        .into_iter()
+        .map(|holiday| {
+            (
+                holidays::created_at.eq(holiday.created_at),
+                holidays::updated_at.eq(holiday.updated_at),
+                holidays::name.eq(holiday.name),
+                holidays::starts_on.eq(holiday.starts_on),
+                holidays::ends_on.eq(holiday.ends_on),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        summary.holidays += diesel::insert_into(holidays::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,397 @@
+use std::{
+    io::Write,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{eyre, Context};
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+
+use crate::{
+    backup,
+    config::Settings,
+    controllers::Actor,
+    db, models,
+    supervisor::{self, Supervisor},
+    Config,
+};
+
+/// Name the changes made from the command line are attributed to
+const CLI_ACTOR: &str = "cli";
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Config file to read, `homeworks.toml` by default if it exists
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub settings: Settings,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Serves the API and the frontend, the default
+    Serve,
+
+    /// Manages the database schema
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+
+    /// Exports all the data as a JSON dump
+    Export {
+        /// Where to write the dump, the standard output by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Imports a JSON dump made by the export
+    Import {
+        /// Path of the dump
+        file: PathBuf,
+
+        /// Replaces all the data with the dump instead of merging them
+        #[arg(long)]
+        replace: bool,
+    },
+
+    /// Lists the homeworks left to do, soonest due first
+    ListHomeworks {
+        /// Also list the homeworks already done
+        #[arg(long)]
+        all: bool,
+
+        /// Only list the homeworks of this subject
+        #[arg(long)]
+        subject: Option<String>,
+    },
+
+    /// Marks homeworks as done
+    MarkDone {
+        /// Ids of the homeworks
+        #[arg(required = true)]
+        ids: Vec<u32>,
+    },
+
+    /// Inspects the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateAction {
+    /// Runs the pending migrations
+    Up,
+
+    /// Reverts the last applied migration
+    Down,
+
+    /// Lists the migrations and whether they are applied
+    Status,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigAction {
+    /// Prints the effective configuration, secrets redacted
+    Check,
+}
+
+pub async fn run(config: &Config, command: Command) -> color_eyre::Result<()> {
+    // Writing rather than printing, so that a closed pipe is not a panic
+    let mut stdout = std::io::stdout();
+
+    match command {
+        Command::Serve => serve(config).await,
+        Command::Migrate { action } => migrate(config, action, &mut stdout).await,
+        Command::Export { output } => export(config, output.as_deref(), &mut stdout).await,
+        Command::Import { file, replace } => import(config, &file, replace, &mut stdout).await,
+        Command::ListHomeworks { all, subject } => {
+            list_homeworks(config, all, subject, &mut stdout).await
+        }
+        Command::MarkDone { ids } => mark_done(config, &ids, &mut stdout).await,
+        Command::Config {
+            action: ConfigAction::Check,
+        } => check_config(config, &mut stdout),
+    }
+}
+
+async fn serve(config: &Config) -> color_eyre::Result<()> {
+    let sockaddr = SocketAddr::new(config.addr, config.port);
+
+    db::run_migrations(config)
+        .await
+        .wrap_err("cannot run migrations")?;
+
+    let supervisor = Supervisor::default();
+
+    let router = crate::create_router(config, &supervisor).await?;
+
+    let listener = tokio::net::TcpListener::bind(sockaddr)
+        .await
+        .wrap_err("cannot bind socket address")?;
+
+    tracing::info!("listening on {sockaddr}");
+
+    let delay = Duration::from_secs(config.shutdown_delay_seconds);
+    let timeout = Duration::from_secs(config.shutdown_timeout_seconds);
+
+    // Not ready first, so that load balancers stop sending requests before
+    // new connections are refused, the workers only stopping once drained
+    let server = axum::serve(listener, router).with_graceful_shutdown({
+        let supervisor = supervisor.clone();
+
+        async move {
+            supervisor::shutdown_signal().await;
+
+            tracing::info!("shutting down, still accepting requests for {delay:?}");
+
+            supervisor.begin_shutdown();
+            tokio::time::sleep(delay).await;
+
+            tracing::info!("waiting up to {timeout:?} for in-flight requests");
+        }
+    });
+
+    // The server only stops once every connection is closed, which could be
+    // never
+    let drain_timeout = async {
+        supervisor.shutting_down().await;
+        tokio::time::sleep(delay + timeout).await;
+    };
+
+    tokio::select! {
+        result = server => result.wrap_err("cannot serve http")?,
+        _ = drain_timeout => tracing::warn!("in-flight requests did not finish in time"),
+    }
+
+    if !supervisor.shutdown(timeout).await {
+        tracing::warn!("background workers did not stop in time");
+    }
+
+    tracing::info!("shut down");
+
+    Ok(())
+}
+
+/// The configuration is already validated once loaded, so it only remains to
+/// print it
+fn check_config(config: &Config, out: &mut impl Write) -> color_eyre::Result<()> {
+    match &config.file {
+        Some(file) => writeln!(out, "# read from {}", file.display())?,
+        None => writeln!(out, "# no config file")?,
+    }
+
+    write!(out, "{}", toml::to_string(&config.redacted())?)?;
+
+    Ok(())
+}
+
+async fn migrate(
+    config: &Config,
+    action: MigrateAction,
+    out: &mut impl Write,
+) -> color_eyre::Result<()> {
+    match action {
+        MigrateAction::Up => db::run_migrations(config).await,
+        MigrateAction::Down => {
+            let version = db::revert_last_migration(config).await?;
+
+            writeln!(out, "reverted {version}")?;
+
+            Ok(())
+        }
+        MigrateAction::Status => {
+            for (name, applied) in db::migration_status(config).await? {
+                let state = if applied { "applied" } else { "pending" };
+
+                writeln!(out, "{state}\t{name}")?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+async fn export(
+    config: &Config,
+    output: Option<&Path>,
+    out: &mut impl Write,
+) -> color_eyre::Result<()> {
+    let mut conn = db::establish_connection(config).await?;
+
+    let export = backup::dump(&mut conn).await.wrap_err("cannot dump data")?;
+    let export = serde_json::to_vec_pretty(&export)?;
+
+    match output {
+        Some(output) => tokio::fs::write(output, export)
+            .await
+            .wrap_err_with(|| format!("cannot write {}", output.display()))?,
+        None => writeln!(out, "{}", String::from_utf8(export)?)?,
+    }
+
+    Ok(())
+}
+
+async fn import(
+    config: &Config,
+    file: &Path,
+    replace: bool,
+    out: &mut impl Write,
+) -> color_eyre::Result<()> {
+    let dump = tokio::fs::read(file)
+        .await
+        .wrap_err_with(|| format!("cannot read {}", file.display()))?;
+
+    let export = serde_json::from_slice::<models::Export>(&dump).wrap_err("invalid dump")?;
+
+    let mode = if replace {
+        models::ImportMode::Replace
+    } else {
+        models::ImportMode::Merge
+    };
+
+    let mut conn = db::establish_connection(config).await?;
+
+    let summary = conn
+        .transaction(|conn| {
+            async move {
+                Actor(Some(CLI_ACTOR.to_owned())).attribute(conn).await?;
+
+                backup::restore(conn, export, mode).await
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|err| eyre!("cannot import dump: {err}"))?;
+
+    writeln!(out, "{}", serde_json::to_string_pretty(&summary)?)?;
+
+    Ok(())
+}
+
+/// Writes a line per homework: its id, due date, subject, status and title
+pub(crate) async fn list_homeworks(
+    config: &Config,
+    all: bool,
+    subject: Option<String>,
+    out: &mut impl Write,
+) -> color_eyre::Result<()> {
+    use crate::schema::homeworks::dsl::*;
+    use crate::schema::subjects;
+
+    let mut query = homeworks
+        .left_join(subjects::table)
+        .select((
+            models::HOMEWORK_ALL_COLUMNS,
+            Option::<models::Subject>::as_select(),
+        ))
+        .filter(deleted_at.is_null())
+        .filter(archived_at.is_null())
+        .filter(subjects::archived_at.is_null())
+        .order_by((due_date.asc().nulls_last(), id))
+        .into_boxed();
+
+    if !all {
+        query = query.filter(done.eq(false));
+    }
+
+    if let Some(subject) = subject {
+        query = query.filter(subjects::name.eq(subject));
+    }
+
+    let mut conn = db::establish_connection(config).await?;
+
+    let results = query
+        .load::<(models::Homework, Option<models::Subject>)>(&mut conn)
+        .await?;
+
+    for (homework, subject) in results {
+        let due = homework.due_date.map_or_else(
+            || "-".to_owned(),
+            |due| {
+                due.with_timezone(&config.timezone)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            },
+        );
+
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}",
+            homework.id,
+            due,
+            subject.map_or_else(|| "-".to_owned(), |subject| subject.name),
+            format!("{:?}", homework.status).to_lowercase(),
+            homework.title,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes a line per homework: its id and whether it was marked as done
+pub(crate) async fn mark_done(
+    config: &Config,
+    ids: &[u32],
+    out: &mut impl Write,
+) -> color_eyre::Result<()> {
+    use crate::schema::homeworks::dsl::*;
+
+    let target_ids = ids
+        .iter()
+        .map(|target_id| *target_id as i32)
+        .collect::<Vec<_>>();
+
+    let mut conn = db::establish_connection(config).await?;
+
+    let (updated, existing) = conn
+        .transaction(|conn| {
+            async move {
+                Actor(Some(CLI_ACTOR.to_owned())).attribute(conn).await?;
+
+                let targets = homeworks
+                    .filter(id.eq_any(&target_ids))
+                    .filter(deleted_at.is_null());
+
+                let existing = targets.clone().select(id).load::<i32>(conn).await?;
+
+                let updated = diesel::update(targets.filter(done.eq(false)))
+                    .set((
+                        status.eq(models::HomeworkStatus::Done),
+                        updated_at.eq(diesel::dsl::now),
+                    ))
+                    .returning(id)
+                    .get_results::<i32>(conn)
+                    .await?;
+
+                QueryResult::Ok((updated, existing))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    for target_id in ids.iter().map(|target_id| *target_id as i32) {
+        let result = if updated.contains(&target_id) {
+            "marked as done"
+        } else if existing.contains(&target_id) {
+            "already done"
+        } else {
+            "not found"
+        };
+
+        writeln!(out, "{target_id}\t{result}")?;
+    }
+
+    Ok(())
+}
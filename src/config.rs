@@ -1,16 +1,240 @@
-use color_eyre::eyre::eyre;
-use serde::Deserialize;
-use std::net::IpAddr;
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-#[derive(Debug, Deserialize)]
-pub struct Config {
-    pub database_url: String,
+use axum::http::HeaderValue;
+use color_eyre::eyre::{eyre, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::{db, jobs};
+
+/// Number of minutes during which an operation can be undone, unless
+/// configured
+pub const DEFAULT_UNDO_WINDOW_MINUTES: u32 = 60;
+
+/// Port the server listens on, unless configured
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// Directory the frontend is served from, unless configured
+pub const DEFAULT_STATIC_DIR: &str = "./dist";
+
+/// Number of seconds to wait for requests and workers on shutdown, unless
+/// configured
+pub const DEFAULT_SHUTDOWN_TIMEOUT_SECONDS: u64 = 30;
+
+/// Number of seconds to keep serving while reported not ready on shutdown,
+/// unless configured
+pub const DEFAULT_SHUTDOWN_DELAY_SECONDS: u64 = 5;
+
+/// File the configuration is read from when it exists, unless another one is
+/// given with `--config` or `CONFIG_FILE`
+pub const DEFAULT_CONFIG_FILE: &str = "homeworks.toml";
+
+/// Minimum length of the token protecting `/api/status`
+const MIN_STATUS_TOKEN_LENGTH: usize = 16;
+
+/// Replaces secrets when printing the configuration
+const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+
+    /// One JSON object per line, for log aggregation
+    Json,
+}
+
+/// How connections to postgres are secured, as `sslmode` in libpq
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    /// Never use TLS
+    Disable,
+
+    /// Use TLS when the server supports it
+    #[default]
+    Prefer,
+
+    /// Always use TLS
+    Require,
+
+    /// Always use TLS, never accepting an invalid server certificate
+    VerifyFull,
+}
+
+impl SslMode {
+    /// Reads an `sslmode` of libpq, `verify-ca` being as strict as
+    /// `verify-full`
+    fn from_libpq(mode: &str) -> Option<Self> {
+        match mode {
+            "disable" => Some(Self::Disable),
+            "allow" | "prefer" => Some(Self::Prefer),
+            "require" => Some(Self::Require),
+            "verify-ca" | "verify-full" => Some(Self::VerifyFull),
+            _ => None,
+        }
+    }
+}
+
+/// Parts of the app that can be turned off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum Feature {
+    /// The frontend, served from the static directory
+    Frontend,
+
+    /// The Swagger UI and the OpenAPI document
+    SwaggerUi,
+
+    /// The iCalendar feed
+    Ical,
+
+    /// The JSON and CSV imports
+    Import,
+
+    /// The periodic purge of the trash
+    TrashPurge,
+
+    /// The Prometheus metrics at `/metrics`
+    Metrics,
+}
+
+/// One layer of settings, every one of them optional
+///
+/// Layers are read from the config file, the environment and the command
+/// line, the latter taking precedence.
+#[derive(Debug, Default, Deserialize, clap::Args)]
+#[command(next_help_heading = "Configuration")]
+pub struct Settings {
+    /// URL of the postgres database
+    #[arg(long, global = true)]
+    pub database_url: Option<String>,
+
+    /// How to secure the connections to the database, the `sslmode` of the
+    /// URL or `prefer` by default
+    #[arg(long, global = true)]
+    pub database_ssl_mode: Option<SslMode>,
+
+    /// Encrypts the connections to the database without verifying its
+    /// certificate, which is never done unless set
+    #[arg(long, global = true)]
+    pub database_ssl_accept_invalid_certs: Option<bool>,
+
+    /// PEM bundle of the certificate authorities trusted to sign the
+    /// certificate of the database, the `sslrootcert` of the URL or the
+    /// platform ones by default
+    #[arg(long, global = true)]
+    pub database_ssl_root_cert: Option<PathBuf>,
+
+    /// PEM certificate to authenticate to the database with, the `sslcert` of
+    /// the URL by default
+    #[arg(long, global = true)]
+    pub database_ssl_cert: Option<PathBuf>,
 
+    /// PEM private key of the client certificate, the `sslkey` of the URL by
+    /// default
+    #[arg(long, global = true)]
+    pub database_ssl_key: Option<PathBuf>,
+
+    /// Address to listen on, all interfaces by default
+    #[arg(long, global = true)]
     pub addr: Option<IpAddr>,
+
+    /// Port to listen on, 8080 by default
+    #[arg(long, global = true)]
     pub port: Option<u16>,
+
+    /// Directory the frontend is served from, `./dist` by default
+    #[arg(long, global = true)]
+    pub static_dir: Option<PathBuf>,
+
+    /// Origins allowed to call the API from a browser, `*` for any
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub cors_origins: Option<Vec<String>>,
+
+    /// Format of the logs
+    #[arg(long, global = true)]
+    pub log_format: Option<LogFormat>,
+
+    /// Base URL of an OTLP/HTTP collector to export traces to, such as
+    /// `http://localhost:4318`, none being exported if unset
+    #[arg(long, global = true)]
+    pub otlp_endpoint: Option<String>,
+
+    /// Bearer token required by `/api/status`, which is disabled without one
+    #[arg(long, global = true)]
+    pub status_token: Option<String>,
+
+    /// Number of seconds to keep accepting requests when shutting down, while
+    /// `/readyz` tells load balancers to stop sending them
+    #[arg(long, global = true)]
+    pub shutdown_delay_seconds: Option<u64>,
+
+    /// Number of seconds to let in-flight requests and background workers
+    /// finish when shutting down
+    #[arg(long, global = true)]
+    pub shutdown_timeout_seconds: Option<u64>,
+
+    /// Timezone the timetable is expressed in, UTC by default
+    #[arg(long, global = true)]
+    pub timezone: Option<chrono_tz::Tz>,
+
+    /// A day of any "A" week of the timetable rotation, weeks alternating
+    /// from the first ISO week of 2024 by default
+    #[arg(long, global = true)]
+    pub timetable_a_week: Option<chrono::NaiveDate>,
+
+    /// Number of days deleted homeworks and subjects stay in the trash
+    #[arg(long, global = true)]
+    pub trash_retention_days: Option<u32>,
+
+    /// Number of minutes between two purges of the trash
+    #[arg(long, global = true)]
+    pub trash_purge_interval_minutes: Option<u32>,
+
+    /// Number of minutes during which an operation can be undone
+    #[arg(long, global = true)]
+    pub undo_window_minutes: Option<u32>,
+
+    /// Maximum number of connections to the database
+    #[arg(long, global = true)]
+    pub pool_max_size: Option<u32>,
+
+    /// Minimum number of idle connections to the database to keep open
+    #[arg(long, global = true)]
+    pub pool_min_idle: Option<u32>,
+
+    /// Number of seconds to wait for a connection to the database
+    #[arg(long, global = true)]
+    pub pool_connection_timeout_seconds: Option<u64>,
+
+    /// Number of seconds after which an idle connection is closed, 0 for never
+    #[arg(long, global = true)]
+    pub pool_idle_timeout_seconds: Option<u64>,
+
+    /// Number of seconds after which a connection is replaced, 0 for never
+    #[arg(long, global = true)]
+    pub pool_max_lifetime_seconds: Option<u64>,
+
+    /// Features to turn off
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub disabled_features: Option<Vec<Feature>>,
 }
 
-impl Config {
+impl Settings {
+    pub fn from_file(path: &Path) -> color_eyre::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("cannot read {}", path.display()))?;
+
+        toml::from_str(&content).wrap_err_with(|| format!("invalid config in {}", path.display()))
+    }
+
     pub fn from_env() -> color_eyre::Result<Self> {
         match envy::from_env::<Self>() {
             Ok(c) => Ok(c),
@@ -23,4 +247,406 @@ impl Config {
             },
         }
     }
+
+    /// Fills the settings missing from this layer with those of `lower`
+    pub fn or(self, lower: Self) -> Self {
+        Self {
+            database_url: self.database_url.or(lower.database_url),
+            database_ssl_mode: self.database_ssl_mode.or(lower.database_ssl_mode),
+            database_ssl_accept_invalid_certs: self
+                .database_ssl_accept_invalid_certs
+                .or(lower.database_ssl_accept_invalid_certs),
+            database_ssl_root_cert: self.database_ssl_root_cert.or(lower.database_ssl_root_cert),
+            database_ssl_cert: self.database_ssl_cert.or(lower.database_ssl_cert),
+            database_ssl_key: self.database_ssl_key.or(lower.database_ssl_key),
+            addr: self.addr.or(lower.addr),
+            port: self.port.or(lower.port),
+            static_dir: self.static_dir.or(lower.static_dir),
+            cors_origins: self.cors_origins.or(lower.cors_origins),
+            log_format: self.log_format.or(lower.log_format),
+            otlp_endpoint: self.otlp_endpoint.or(lower.otlp_endpoint),
+            status_token: self.status_token.or(lower.status_token),
+            shutdown_delay_seconds: self.shutdown_delay_seconds.or(lower.shutdown_delay_seconds),
+            shutdown_timeout_seconds: self
+                .shutdown_timeout_seconds
+                .or(lower.shutdown_timeout_seconds),
+            timezone: self.timezone.or(lower.timezone),
+            timetable_a_week: self.timetable_a_week.or(lower.timetable_a_week),
+            trash_retention_days: self.trash_retention_days.or(lower.trash_retention_days),
+            trash_purge_interval_minutes: self
+                .trash_purge_interval_minutes
+                .or(lower.trash_purge_interval_minutes),
+            undo_window_minutes: self.undo_window_minutes.or(lower.undo_window_minutes),
+            pool_max_size: self.pool_max_size.or(lower.pool_max_size),
+            pool_min_idle: self.pool_min_idle.or(lower.pool_min_idle),
+            pool_connection_timeout_seconds: self
+                .pool_connection_timeout_seconds
+                .or(lower.pool_connection_timeout_seconds),
+            pool_idle_timeout_seconds: self
+                .pool_idle_timeout_seconds
+                .or(lower.pool_idle_timeout_seconds),
+            pool_max_lifetime_seconds: self
+                .pool_max_lifetime_seconds
+                .or(lower.pool_max_lifetime_seconds),
+            disabled_features: self.disabled_features.or(lower.disabled_features),
+        }
+    }
+}
+
+/// The effective configuration, every setting resolved to its value or its
+/// default
+#[derive(Debug, Clone, Serialize)]
+pub struct Config {
+    /// File the configuration was read from, if any
+    #[serde(skip)]
+    pub file: Option<PathBuf>,
+
+    /// Connection string, without the TLS parameters read from it
+    pub database_url: String,
+    pub database_ssl_mode: SslMode,
+    pub database_ssl_accept_invalid_certs: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_ssl_root_cert: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_ssl_cert: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_ssl_key: Option<PathBuf>,
+
+    pub addr: IpAddr,
+    pub port: u16,
+
+    pub static_dir: PathBuf,
+    pub cors_origins: Vec<String>,
+    pub log_format: LogFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otlp_endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_token: Option<String>,
+    pub shutdown_delay_seconds: u64,
+    pub shutdown_timeout_seconds: u64,
+
+    pub timezone: chrono_tz::Tz,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timetable_a_week: Option<chrono::NaiveDate>,
+
+    pub trash_retention_days: u32,
+    pub trash_purge_interval_minutes: u32,
+    pub undo_window_minutes: u32,
+
+    pub pool_max_size: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_min_idle: Option<u32>,
+    pub pool_connection_timeout_seconds: u64,
+    pub pool_idle_timeout_seconds: u64,
+    pub pool_max_lifetime_seconds: u64,
+
+    pub disabled_features: Vec<Feature>,
+}
+
+impl Config {
+    /// Reads the settings from the config file, overridden by the environment,
+    /// overridden by `flags`
+    pub fn load(file: Option<&Path>, flags: Settings) -> color_eyre::Result<Self> {
+        let file = file
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os("CONFIG_FILE").map(PathBuf::from))
+            .or_else(|| {
+                let default = PathBuf::from(DEFAULT_CONFIG_FILE);
+                default.is_file().then_some(default)
+            });
+
+        let from_file = match &file {
+            Some(file) => Settings::from_file(file)?,
+            None => Settings::default(),
+        };
+
+        let mut config = Self::try_from(flags.or(Settings::from_env()?).or(from_file))?;
+        config.file = file;
+
+        Ok(config)
+    }
+
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        !self.disabled_features.contains(&feature)
+    }
+
+    /// A copy of the configuration that is safe to print
+    pub fn redacted(&self) -> Self {
+        Self {
+            database_url: redact_database_url(&self.database_url),
+            status_token: self.status_token.as_ref().map(|_| REDACTED.to_owned()),
+            ..self.clone()
+        }
+    }
+
+    /// Checks the settings, reporting all the invalid ones at once
+    fn validate(&self) -> color_eyre::Result<()> {
+        let mut errors = Vec::new();
+
+        if self.database_url.is_empty() {
+            errors.push("database_url is required".to_owned());
+        } else if let Err(err) = tokio_postgres::Config::from_str(&self.database_url) {
+            errors.push(format!("database_url is not a valid postgres url: {err}"));
+        }
+
+        match (&self.database_ssl_cert, &self.database_ssl_key) {
+            (Some(_), None) => {
+                errors.push("database_ssl_cert requires database_ssl_key".to_owned())
+            }
+            (None, Some(_)) => {
+                errors.push("database_ssl_key requires database_ssl_cert".to_owned())
+            }
+            _ => {}
+        }
+
+        if self.database_ssl_accept_invalid_certs {
+            if self.database_ssl_mode == SslMode::VerifyFull {
+                errors.push(
+                    "database_ssl_accept_invalid_certs cannot be set in verify-full mode"
+                        .to_owned(),
+                );
+            }
+
+            if self.database_ssl_root_cert.is_some() {
+                errors.push(
+                    "database_ssl_root_cert is not used with database_ssl_accept_invalid_certs"
+                        .to_owned(),
+                );
+            }
+        }
+
+        for (name, path) in [
+            ("database_ssl_root_cert", &self.database_ssl_root_cert),
+            ("database_ssl_cert", &self.database_ssl_cert),
+            ("database_ssl_key", &self.database_ssl_key),
+        ] {
+            if let Some(path) = path.as_ref().filter(|path| !path.is_file()) {
+                errors.push(format!("{name} is not a file: {}", path.display()));
+            }
+        }
+
+        for origin in &self.cors_origins {
+            if origin == "*" {
+                if self.cors_origins.len() > 1 {
+                    errors.push("cors_origins cannot mix `*` with other origins".to_owned());
+                }
+            } else if !(origin.starts_with("http://") || origin.starts_with("https://"))
+                || HeaderValue::from_str(origin).is_err()
+            {
+                errors.push(format!("cors_origins has an invalid origin: {origin}"));
+            }
+        }
+
+        if let Some(endpoint) = self.otlp_endpoint.as_ref().filter(|endpoint| {
+            !(endpoint.starts_with("http://") || endpoint.starts_with("https://"))
+        }) {
+            errors.push(format!("otlp_endpoint is not an http(s) URL: {endpoint}"));
+        }
+
+        if self
+            .status_token
+            .as_ref()
+            .is_some_and(|token| token.len() < MIN_STATUS_TOKEN_LENGTH)
+        {
+            errors.push(format!(
+                "status_token must be at least {MIN_STATUS_TOKEN_LENGTH} characters long"
+            ));
+        }
+
+        if self.trash_purge_interval_minutes == 0 {
+            errors.push("trash_purge_interval_minutes must be at least 1".to_owned());
+        }
+
+        if self.pool_max_size == 0 {
+            errors.push("pool_max_size must be at least 1".to_owned());
+        }
+
+        if self
+            .pool_min_idle
+            .is_some_and(|min_idle| min_idle > self.pool_max_size)
+        {
+            errors.push("pool_min_idle cannot exceed pool_max_size".to_owned());
+        }
+
+        if self.pool_connection_timeout_seconds == 0 {
+            errors.push("pool_connection_timeout_seconds must be at least 1".to_owned());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("invalid configuration:\n  {}", errors.join("\n  ")))
+        }
+    }
+}
+
+impl TryFrom<Settings> for Config {
+    type Error = color_eyre::Report;
+
+    fn try_from(settings: Settings) -> Result<Self, Self::Error> {
+        let (database_url, url_ssl) = take_ssl_params(&settings.database_url.unwrap_or_default());
+
+        let config = Self {
+            file: None,
+            database_url,
+            database_ssl_mode: settings
+                .database_ssl_mode
+                .or(url_ssl.mode)
+                .unwrap_or_default(),
+            database_ssl_accept_invalid_certs: settings
+                .database_ssl_accept_invalid_certs
+                .unwrap_or_default(),
+            database_ssl_root_cert: settings.database_ssl_root_cert.or(url_ssl.root_cert),
+            database_ssl_cert: settings.database_ssl_cert.or(url_ssl.cert),
+            database_ssl_key: settings.database_ssl_key.or(url_ssl.key),
+            addr: settings.addr.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            port: settings.port.unwrap_or(DEFAULT_PORT),
+            static_dir: settings
+                .static_dir
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_STATIC_DIR)),
+            cors_origins: settings.cors_origins.unwrap_or_default(),
+            log_format: settings.log_format.unwrap_or_default(),
+            otlp_endpoint: settings.otlp_endpoint,
+            status_token: settings.status_token,
+            shutdown_delay_seconds: settings
+                .shutdown_delay_seconds
+                .unwrap_or(DEFAULT_SHUTDOWN_DELAY_SECONDS),
+            shutdown_timeout_seconds: settings
+                .shutdown_timeout_seconds
+                .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECONDS),
+            timezone: settings.timezone.unwrap_or(chrono_tz::UTC),
+            timetable_a_week: settings.timetable_a_week,
+            trash_retention_days: settings
+                .trash_retention_days
+                .unwrap_or(jobs::DEFAULT_TRASH_RETENTION_DAYS),
+            trash_purge_interval_minutes: settings
+                .trash_purge_interval_minutes
+                .unwrap_or(jobs::DEFAULT_TRASH_PURGE_INTERVAL_MINUTES),
+            undo_window_minutes: settings
+                .undo_window_minutes
+                .unwrap_or(DEFAULT_UNDO_WINDOW_MINUTES),
+            pool_max_size: settings.pool_max_size.unwrap_or(db::DEFAULT_POOL_MAX_SIZE),
+            pool_min_idle: settings.pool_min_idle,
+            pool_connection_timeout_seconds: settings
+                .pool_connection_timeout_seconds
+                .unwrap_or(db::DEFAULT_POOL_CONNECTION_TIMEOUT_SECONDS),
+            pool_idle_timeout_seconds: settings
+                .pool_idle_timeout_seconds
+                .unwrap_or(db::DEFAULT_POOL_IDLE_TIMEOUT_SECONDS),
+            pool_max_lifetime_seconds: settings
+                .pool_max_lifetime_seconds
+                .unwrap_or(db::DEFAULT_POOL_MAX_LIFETIME_SECONDS),
+            disabled_features: settings.disabled_features.unwrap_or_default(),
+        };
+
+        config.validate()?;
+
+        Ok(config)
+    }
+}
+
+/// TLS parameters of a connection string
+#[derive(Debug, Default)]
+struct UrlSslParams {
+    mode: Option<SslMode>,
+    root_cert: Option<PathBuf>,
+    cert: Option<PathBuf>,
+    key: Option<PathBuf>,
+}
+
+/// Takes the TLS parameters out of a postgres URL or key-value connection
+/// string, tokio-postgres rejecting most of those libpq accepts
+fn take_ssl_params(url: &str) -> (String, UrlSslParams) {
+    let mut params = UrlSslParams::default();
+
+    // Whether a pair is left to tokio-postgres, an unknown mode being so that
+    // it reports it
+    let mut keep = |pair: &str, decode: bool| {
+        let Some((key, value)) = pair.split_once('=') else {
+            return true;
+        };
+
+        let value = if decode {
+            percent_encoding::percent_decode_str(value)
+                .decode_utf8_lossy()
+                .into_owned()
+        } else {
+            value.to_owned()
+        };
+
+        match key {
+            "sslmode" => match SslMode::from_libpq(&value) {
+                Some(mode) => params.mode = Some(mode),
+                None => return true,
+            },
+            "sslrootcert" => params.root_cert = Some(value.into()),
+            "sslcert" => params.cert = Some(value.into()),
+            "sslkey" => params.key = Some(value.into()),
+            _ => return true,
+        }
+
+        false
+    };
+
+    let url = match url.split_once("://") {
+        Some((scheme, rest)) => match rest.split_once('?') {
+            Some((rest, query)) => {
+                let query = query
+                    .split('&')
+                    .filter(|pair| keep(pair, true))
+                    .collect::<Vec<_>>()
+                    .join("&");
+
+                if query.is_empty() {
+                    format!("{scheme}://{rest}")
+                } else {
+                    format!("{scheme}://{rest}?{query}")
+                }
+            }
+            None => url.to_owned(),
+        },
+        None => url
+            .split(' ')
+            .filter(|pair| keep(pair, false))
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+
+    (url, params)
+}
+
+/// Hides the password of a postgres URL or key-value connection string
+fn redact_database_url(url: &str) -> String {
+    let redact_pairs = |pairs: &str, separator: char| {
+        pairs
+            .split(separator)
+            .map(|pair| match pair.split_once('=') {
+                Some(("password", _)) => format!("password={REDACTED}"),
+                _ => pair.to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join(&separator.to_string())
+    };
+
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return redact_pairs(url, ' ');
+    };
+
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(redact_pairs(query, '&'))),
+        None => (rest, None),
+    };
+
+    let rest = match rest.rsplit_once('@') {
+        Some((userinfo, host)) => match userinfo.split_once(':') {
+            Some((user, _)) => format!("{user}:{REDACTED}@{host}"),
+            None => format!("{userinfo}@{host}"),
+        },
+        None => rest.to_owned(),
+    };
+
+    match query {
+        Some(query) => format!("{scheme}://{rest}?{query}"),
+        None => format!("{scheme}://{rest}"),
+    }
 }
@@ -0,0 +1,354 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+use serde::Deserialize;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use super::history::Actor;
+use crate::{
+    errors::{not_found, unprocessable_entity, AppResult},
+    models, utils, AppState,
+};
+
+const TAG: &str = "Exams";
+
+/// Number of revision homeworks generated when not specified
+const DEFAULT_REVISION_DAYS: u32 = 3;
+
+/// Maximum number of revision homeworks generated for an exam
+const MAX_REVISION_DAYS: u32 = 60;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_exams, create_exam))
+        .routes(routes!(get_exam, update_exam, delete_exam))
+        .routes(routes!(generate_exam_revisions))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct ListExamsParams {
+    /// Only return exams starting after `start`
+    start: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only return exams starting before `end`
+    end: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Filter by subjects
+    subject_ids: Option<utils::IdSequence>,
+}
+
+/// Replaces the pending revision homeworks of an exam by one revision on
+/// each of the `days` days before it, the replaced ones going to the trash
+async fn generate_revisions(
+    conn: &mut AsyncPgConnection,
+    exam: &models::Exam,
+    days: u32,
+) -> QueryResult<Vec<models::Homework>> {
+    use crate::schema::homeworks;
+
+    let replaced = diesel::update(homeworks::table)
+        .filter(homeworks::exam_id.eq(exam.id))
+        .filter(homeworks::status.eq(models::HomeworkStatus::Todo))
+        .filter(homeworks::deleted_at.is_null())
+        .set(homeworks::deleted_at.eq(diesel::dsl::now))
+        .returning(homeworks::id)
+        .get_results::<i32>(conn)
+        .await?;
+
+    super::time_entries::stop_timers(conn, &replaced).await?;
+
+    let now = chrono::Utc::now();
+
+    let title = format!("Revision: {}", exam.title);
+    let description = if exam.chapters.is_empty() {
+        String::new()
+    } else {
+        format!("Chapters: {}", exam.chapters.join(", "))
+    };
+
+    let revisions = (1..=days)
+        .rev()
+        .filter_map(|days_before| {
+            exam.starts_at
+                .checked_sub_signed(chrono::Duration::days(days_before.into()))
+        })
+        .filter(|due| *due > now)
+        .map(|due| {
+            (
+                homeworks::title.eq(&title),
+                homeworks::description.eq(&description),
+                homeworks::due_date.eq(due),
+                homeworks::subject_id.eq(exam.subject_id),
+                homeworks::exam_id.eq(exam.id),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    diesel::insert_into(homeworks::table)
+        .values(&revisions)
+        .returning(models::Homework::as_returning())
+        .get_results(conn)
+        .await
+}
+
+/// Retrieves all the exams
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    params(
+        ListExamsParams
+    ),
+    responses(
+        (status = OK, body = [models::ExamWithSubject])
+    )
+)]
+async fn list_exams(
+    State(state): State<AppState>,
+    Query(params): Query<ListExamsParams>,
+) -> AppResult<Json<Vec<models::ExamWithSubject>>> {
+    use crate::schema::exams::dsl::*;
+    use crate::schema::subjects;
+
+    let mut query = exams
+        .left_join(subjects::table)
+        .select((
+            models::Exam::as_select(),
+            Option::<models::Subject>::as_select(),
+        ))
+        .order_by(starts_at)
+        .into_boxed();
+
+    if let Some(start) = params.start {
+        query = query.filter(starts_at.ge(start));
+    }
+
+    if let Some(end) = params.end {
+        query = query.filter(starts_at.le(end));
+    }
+
+    if let Some(subject_ids) = params.subject_ids {
+        if !subject_ids.is_empty() {
+            query = query.filter(subject_id.eq_any(subject_ids.ids()));
+        }
+    }
+
+    let mut conn = state.pool.get().await?;
+
+    let results = query
+        .load::<(models::Exam, Option<models::Subject>)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(exam, subject)| models::ExamWithSubject { exam, subject })
+        .collect();
+
+    Ok(Json(results))
+}
+
+/// Retrieves a specific exam
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::ExamWithSubject),
+        (status = NOT_FOUND, description = "The exam does not exist")
+    ),
+    params(
+        ("id", description = "Id of the exam"),
+    )
+)]
+async fn get_exam(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::ExamWithSubject>> {
+    use crate::schema::exams;
+    use crate::schema::subjects;
+
+    let mut conn = state.pool.get().await?;
+
+    let (exam, subject) = exams::table
+        .find(target_id as i32)
+        .left_join(subjects::table)
+        .select((
+            models::Exam::as_select(),
+            Option::<models::Subject>::as_select(),
+        ))
+        .first::<(models::Exam, Option<models::Subject>)>(&mut conn)
+        .await?;
+
+    Ok(Json(models::ExamWithSubject { exam, subject }))
+}
+
+/// Creates a new exam
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Exam),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject does not exist or is in the trash, or revision_days is above 60")
+    )
+)]
+async fn create_exam(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(payload): Json<models::NewExam>,
+) -> AppResult<Json<models::Exam>> {
+    use crate::schema::exams;
+
+    if payload
+        .revision_days
+        .is_some_and(|days| days > MAX_REVISION_DAYS)
+    {
+        return Err(unprocessable_entity());
+    }
+
+    let mut conn = state.pool.get().await?;
+
+    super::homeworks::check_subject(&mut conn, payload.subject_id).await?;
+
+    let new_exam = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let new_exam = diesel::insert_into(exams::table)
+                    .values(&payload)
+                    .returning(models::Exam::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                if let Some(days) = payload.revision_days {
+                    generate_revisions(conn, &new_exam, days).await?;
+                }
+
+                QueryResult::Ok(new_exam)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(new_exam))
+}
+
+/// Updates an exam
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Exam),
+        (status = NOT_FOUND, description = "The exam does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject does not exist or is in the trash")
+    ),
+    params(
+        ("id", description = "Id of the exam"),
+    )
+)]
+async fn update_exam(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::UpdatedExam>,
+) -> AppResult<Json<models::Exam>> {
+    use crate::schema::exams::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    super::homeworks::check_subject(&mut conn, payload.subject_id).await?;
+
+    let updated_exam = diesel::update(exams)
+        .filter(id.eq(target_id as i32))
+        .set(&payload)
+        .returning(models::Exam::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(updated_exam))
+}
+
+/// Deletes an exam
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, description = "The exam does not exist")
+    ),
+    params(
+        ("id", description = "Id of the exam"),
+    )
+)]
+async fn delete_exam(State(state): State<AppState>, Path(target_id): Path<u32>) -> AppResult<()> {
+    use crate::schema::exams::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let deleted_rows = diesel::delete(exams.filter(id.eq(target_id as i32)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted_rows == 0 {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
+
+/// Schedules revision homeworks in the days before an exam
+///
+/// Revisions previously generated for the exam and not started yet are
+/// replaced, and moved to the trash.
+#[utoipa::path(
+    post,
+    path = "/{id}/revisions",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::Homework]),
+        (status = NOT_FOUND, description = "The exam does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "More than 60 days are asked")
+    ),
+    params(
+        ("id", description = "Id of the exam"),
+    )
+)]
+async fn generate_exam_revisions(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    actor: Actor,
+    Json(payload): Json<models::RevisionPlan>,
+) -> AppResult<Json<Vec<models::Homework>>> {
+    use crate::schema::exams;
+
+    let days = payload.days.unwrap_or(DEFAULT_REVISION_DAYS);
+
+    if days > MAX_REVISION_DAYS {
+        return Err(unprocessable_entity());
+    }
+
+    let mut conn = state.pool.get().await?;
+
+    let revisions = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let exam = exams::table
+                    .find(target_id as i32)
+                    .first::<models::Exam>(conn)
+                    .await?;
+
+                generate_revisions(conn, &exam, days).await
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(revisions))
+}
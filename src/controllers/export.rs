@@ -0,0 +1,149 @@
+use axum::{
+    body::Body,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    BoxError, Json,
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use futures_util::{stream, StreamExt};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{backup, errors::AppResult, models, AppState};
+
+const TAG: &str = "Export";
+
+/// Number of homeworks fetched at once while streaming the CSV export
+const CSV_PAGE_SIZE: i64 = 500;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(export_json))
+        .routes(routes!(export_homeworks_csv))
+}
+
+/// Exports all the data as JSON
+///
+/// The dump carries a `schema_version`, bumped whenever its layout changes.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Export)
+    )
+)]
+async fn export_json(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let mut conn = state.pool.get().await?;
+
+    let export = backup::dump(&mut conn).await?;
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"homeworks.json\"",
+        )],
+        Json(export),
+    ))
+}
+
+/// Serializes homeworks to CSV, preceded by the header if `with_header` is set
+fn write_csv(
+    results: Vec<models::HomeworkWithSubject>,
+    with_header: bool,
+) -> Result<Vec<u8>, BoxError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(with_header)
+        .from_writer(Vec::new());
+
+    for result in results {
+        let tags = result
+            .tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+
+        writer.serialize(models::HomeworkCsvRow {
+            id: result.homework.id,
+            title: result.homework.title,
+            description: result.homework.description,
+            subject: result.subject.map(|subject| subject.name),
+            due_date: result.homework.due_date,
+            status: result.homework.status,
+            priority: result.homework.priority,
+            estimated_minutes: result.homework.estimated_minutes,
+            difficulty: result.homework.difficulty,
+            tags,
+            tracked_seconds: result.tracked_seconds,
+            created_at: result.homework.created_at,
+            updated_at: result.homework.updated_at,
+            archived_at: result.homework.archived_at,
+        })?;
+    }
+
+    Ok(writer.into_inner().map_err(|err| err.into_error())?)
+}
+
+/// Exports the homeworks as CSV, with the names of their subjects
+///
+/// Homeworks in the trash are left out. The file is streamed page by page.
+#[utoipa::path(
+    get,
+    path = "/homeworks.csv",
+    tag = TAG,
+    responses(
+        (status = OK, content_type = "text/csv", body = String)
+    )
+)]
+async fn export_homeworks_csv(State(state): State<AppState>) -> Response {
+    let pages = stream::try_unfold(Some(0), move |after| {
+        let pool = state.pool.clone();
+
+        async move {
+            use crate::schema::homeworks::dsl::*;
+            use crate::schema::subjects;
+
+            let Some(after) = after else {
+                return Ok(None);
+            };
+
+            let mut conn = pool.get().await?;
+
+            let results = homeworks
+                .left_join(subjects::table)
+                .select((
+                    models::HOMEWORK_ALL_COLUMNS,
+                    Option::<models::Subject>::as_select(),
+                ))
+                .filter(id.gt(after))
+                .filter(deleted_at.is_null())
+                .order_by(id)
+                .limit(CSV_PAGE_SIZE)
+                .load::<(models::Homework, Option<models::Subject>)>(&mut conn)
+                .await?;
+
+            let next = match results.last() {
+                Some((homework, _)) if results.len() as i64 == CSV_PAGE_SIZE => Some(homework.id),
+                _ => None,
+            };
+
+            let results = super::homeworks::with_details(&mut conn, results).await?;
+
+            Ok::<_, BoxError>(Some((write_csv(results, after == 0)?, next)))
+        }
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"homeworks.csv\"",
+            ),
+        ],
+        Body::from_stream(pages.boxed()),
+    )
+        .into_response()
+}
@@ -0,0 +1,356 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::Deserialize;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    errors::{not_found, unprocessable_entity, AppResult},
+    models, AppState,
+};
+
+const TAG: &str = "Grades";
+
+/// Routes nested under a subject
+pub fn subject_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_grades, create_grade))
+        .routes(routes!(subject_average))
+}
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(averages))
+        .routes(routes!(get_grade, update_grade, delete_grade))
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub(super) struct AverageParams {
+    /// Only count grades given on or after `start`
+    start: Option<chrono::NaiveDate>,
+
+    /// Only count grades given on or before `end`
+    end: Option<chrono::NaiveDate>,
+
+    /// Only count grades given during a term
+    term_id: Option<u32>,
+
+    /// Scale of the averages, 20 by default
+    scale: Option<f64>,
+}
+
+impl AverageParams {
+    /// Rejects the scales that would give meaningless averages
+    fn validate(&self) -> AppResult<()> {
+        match self.scale {
+            Some(scale) if !scale.is_finite() || scale <= 0.0 => Err(unprocessable_entity()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Computes the weighted average of each subject that has grades
+pub(super) async fn subject_averages(
+    conn: &mut AsyncPgConnection,
+    subject_ids: Option<Vec<i32>>,
+    params: &AverageParams,
+) -> QueryResult<Vec<models::SubjectAverage>> {
+    use crate::schema::grades::dsl::*;
+    use crate::schema::{subjects, terms};
+    use diesel::sql_types::{Double, Nullable};
+
+    let trashed_subjects = subjects::table
+        .filter(subjects::deleted_at.is_not_null())
+        .select(subjects::id);
+
+    let mut query = grades
+        .filter(subject_id.ne_all(trashed_subjects))
+        .group_by(subject_id)
+        .select((
+            subject_id,
+            diesel::dsl::count(id),
+            diesel::dsl::sql::<Nullable<Double>>(
+                "SUM(coefficient * score / max_score) / SUM(coefficient)",
+            ),
+        ))
+        .order_by(subject_id)
+        .into_boxed();
+
+    if let Some(subject_ids) = subject_ids {
+        query = query.filter(subject_id.eq_any(subject_ids));
+    }
+
+    if let Some(start) = params.start {
+        query = query.filter(graded_on.ge(start));
+    }
+
+    if let Some(end) = params.end {
+        query = query.filter(graded_on.le(end));
+    }
+
+    if let Some(term_id) = params.term_id {
+        let term = terms::table
+            .find(term_id as i32)
+            .first::<models::Term>(conn)
+            .await?;
+
+        query = query.filter(graded_on.between(term.starts_on, term.ends_on));
+    }
+
+    let scale = params.scale.unwrap_or(models::DEFAULT_GRADE_SCALE);
+
+    let results = query
+        .load::<(i32, i64, Option<f64>)>(conn)
+        .await?
+        .into_iter()
+        .map(|(subject, count, ratio)| models::SubjectAverage {
+            subject_id: subject,
+            grades: count,
+            average: ratio.map(|ratio| ratio * scale),
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Retrieves the grades of a subject
+#[utoipa::path(
+    get,
+    path = "/{id}/grades",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::Grade]),
+        (status = NOT_FOUND, description = "The subject does not exist")
+    ),
+    params(
+        ("id", description = "Id of the subject"),
+    )
+)]
+async fn list_grades(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<Vec<models::Grade>>> {
+    use crate::schema::grades;
+    use crate::schema::subjects;
+
+    let mut conn = state.pool.get().await?;
+
+    let subject = subjects::table
+        .find(target_id as i32)
+        .filter(subjects::deleted_at.is_null())
+        .first::<models::Subject>(&mut conn)
+        .await?;
+
+    let results = models::Grade::belonging_to(&subject)
+        .order_by((grades::graded_on, grades::id))
+        .load::<models::Grade>(&mut conn)
+        .await?;
+
+    Ok(Json(results))
+}
+
+/// Records a grade in a subject
+#[utoipa::path(
+    post,
+    path = "/{id}/grades",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Grade),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject or the homework does not exist")
+    ),
+    params(
+        ("id", description = "Id of the subject"),
+    )
+)]
+async fn create_grade(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::NewGrade>,
+) -> AppResult<Json<models::Grade>> {
+    use crate::schema::grades;
+
+    let mut conn = state.pool.get().await?;
+
+    let new_grade = diesel::insert_into(grades::table)
+        .values((&payload, grades::subject_id.eq(target_id as i32)))
+        .returning(models::Grade::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(new_grade))
+}
+
+/// Computes the weighted average of a subject
+#[utoipa::path(
+    get,
+    path = "/{id}/average",
+    tag = TAG,
+    params(
+        ("id", description = "Id of the subject"),
+        AverageParams
+    ),
+    responses(
+        (status = OK, body = models::SubjectAverage),
+        (status = NOT_FOUND, description = "The subject does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The scale is not positive")
+    )
+)]
+async fn subject_average(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Query(params): Query<AverageParams>,
+) -> AppResult<Json<models::SubjectAverage>> {
+    use crate::schema::subjects;
+
+    params.validate()?;
+
+    let mut conn = state.pool.get().await?;
+
+    let subject_id = subjects::table
+        .find(target_id as i32)
+        .filter(subjects::deleted_at.is_null())
+        .select(subjects::id)
+        .first::<i32>(&mut conn)
+        .await?;
+
+    let average = subject_averages(&mut conn, Some(vec![subject_id]), &params)
+        .await?
+        .pop()
+        .unwrap_or(models::SubjectAverage {
+            subject_id,
+            grades: 0,
+            average: None,
+        });
+
+    Ok(Json(average))
+}
+
+/// Computes the weighted averages of every subject, and the overall average
+#[utoipa::path(
+    get,
+    path = "/averages",
+    tag = TAG,
+    params(
+        AverageParams
+    ),
+    responses(
+        (status = OK, body = models::GradeAverages),
+        (status = UNPROCESSABLE_ENTITY, description = "The scale is not positive")
+    )
+)]
+async fn averages(
+    State(state): State<AppState>,
+    Query(params): Query<AverageParams>,
+) -> AppResult<Json<models::GradeAverages>> {
+    params.validate()?;
+
+    let mut conn = state.pool.get().await?;
+
+    let subjects = subject_averages(&mut conn, None, &params).await?;
+
+    let subject_averages = subjects
+        .iter()
+        .filter_map(|subject| subject.average)
+        .collect::<Vec<_>>();
+
+    let overall = if subject_averages.is_empty() {
+        None
+    } else {
+        Some(subject_averages.iter().sum::<f64>() / subject_averages.len() as f64)
+    };
+
+    Ok(Json(models::GradeAverages { overall, subjects }))
+}
+
+/// Retrieves a specific grade
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Grade),
+        (status = NOT_FOUND, description = "The grade does not exist")
+    ),
+    params(
+        ("id", description = "Id of the grade"),
+    )
+)]
+async fn get_grade(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::Grade>> {
+    use crate::schema::grades;
+
+    let mut conn = state.pool.get().await?;
+
+    let grade = grades::table
+        .find(target_id as i32)
+        .first::<models::Grade>(&mut conn)
+        .await?;
+
+    Ok(Json(grade))
+}
+
+/// Updates a grade
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Grade),
+        (status = NOT_FOUND, description = "The grade does not exist")
+    ),
+    params(
+        ("id", description = "Id of the grade"),
+    )
+)]
+async fn update_grade(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::UpdatedGrade>,
+) -> AppResult<Json<models::Grade>> {
+    use crate::schema::grades::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let updated_grade = diesel::update(grades)
+        .filter(id.eq(target_id as i32))
+        .set(&payload)
+        .returning(models::Grade::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(updated_grade))
+}
+
+/// Deletes a grade
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, description = "The grade does not exist")
+    ),
+    params(
+        ("id", description = "Id of the grade"),
+    )
+)]
+async fn delete_grade(State(state): State<AppState>, Path(target_id): Path<u32>) -> AppResult<()> {
+    use crate::schema::grades::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let deleted_rows = diesel::delete(grades.filter(id.eq(target_id as i32)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted_rows == 0 {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use axum::{
+    extract::{FromRequestParts, Path, State},
+    http::request::Parts,
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    errors::{not_found, AppResult},
+    models, AppState,
+};
+
+const TAG: &str = "History";
+
+/// Header naming who makes a request
+const ACTOR_HEADER: &str = "x-actor";
+
+/// Routes nested under a homework
+pub fn homework_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(homework_history))
+}
+
+/// Routes nested under a subject
+pub fn subject_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(subject_history))
+}
+
+/// Who makes a request, as given by the `X-Actor` header
+#[derive(Debug, Clone)]
+pub(crate) struct Actor(pub(crate) Option<String>);
+
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let actor = parts
+            .headers
+            .get(ACTOR_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+
+        Ok(Self(actor))
+    }
+}
+
+impl Actor {
+    /// Attributes the changes of the current transaction to the actor
+    pub(crate) async fn attribute(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        use diesel::sql_types::Text;
+
+        diesel::sql_query("SELECT set_config('homeworks.actor', $1, true)")
+            .bind::<Text, _>(self.0.as_deref().unwrap_or_default())
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Retrieves the history of a record, oldest change first
+///
+/// `exists` tells whether the record is still there, trashed or not.
+async fn record_history(
+    conn: &mut AsyncPgConnection,
+    target_table: &str,
+    target_id: i32,
+    exists: bool,
+) -> AppResult<Vec<models::HistoryEntry>> {
+    use crate::schema::history_entries::dsl::*;
+
+    let results = history_entries
+        .filter(table_name.eq(target_table))
+        .filter(record_id.eq(target_id))
+        .order_by(id)
+        .load::<models::HistoryEntry>(conn)
+        .await?;
+
+    // Purged records keep their history, and records made before it was
+    // recorded have none
+    if results.is_empty() && !exists {
+        return Err(not_found());
+    }
+
+    Ok(results)
+}
+
+/// Retrieves every change made to a homework
+#[utoipa::path(
+    get,
+    path = "/{id}/history",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::HistoryEntry]),
+        (status = NOT_FOUND, description = "The homework does not exist and has no history")
+    ),
+    params(
+        ("id", description = "Id of the homework"),
+    )
+)]
+async fn homework_history(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<Vec<models::HistoryEntry>>> {
+    use crate::schema::homeworks;
+
+    let mut conn = state.pool.get().await?;
+
+    let exists = diesel::select(diesel::dsl::exists(homeworks::table.find(target_id as i32)))
+        .get_result::<bool>(&mut conn)
+        .await?;
+
+    let results = record_history(&mut conn, "homeworks", target_id as i32, exists).await?;
+
+    Ok(Json(results))
+}
+
+/// Retrieves every change made to a subject
+#[utoipa::path(
+    get,
+    path = "/{id}/history",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::HistoryEntry]),
+        (status = NOT_FOUND, description = "The subject does not exist and has no history")
+    ),
+    params(
+        ("id", description = "Id of the subject"),
+    )
+)]
+async fn subject_history(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<Vec<models::HistoryEntry>>> {
+    use crate::schema::subjects;
+
+    let mut conn = state.pool.get().await?;
+
+    let exists = diesel::select(diesel::dsl::exists(subjects::table.find(target_id as i32)))
+        .get_result::<bool>(&mut conn)
+        .await?;
+
+    let results = record_history(&mut conn, "subjects", target_id as i32, exists).await?;
+
+    Ok(Json(results))
+}
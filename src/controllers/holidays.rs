@@ -0,0 +1,163 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    errors::{not_found, AppResult},
+    models, AppState,
+};
+
+const TAG: &str = "Holidays";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_holidays, create_holiday))
+        .routes(routes!(get_holiday, update_holiday, delete_holiday))
+}
+
+/// Retrieves all the holiday periods
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::Holiday])
+    )
+)]
+async fn list_holidays(State(state): State<AppState>) -> AppResult<Json<Vec<models::Holiday>>> {
+    use crate::schema::holidays::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let results = holidays
+        .order_by((starts_on, id))
+        .load::<models::Holiday>(&mut conn)
+        .await?;
+
+    Ok(Json(results))
+}
+
+/// Retrieves a specific holiday period
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Holiday),
+        (status = NOT_FOUND, description = "The holiday period does not exist")
+    ),
+    params(
+        ("id", description = "Id of the holiday period"),
+    )
+)]
+async fn get_holiday(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::Holiday>> {
+    use crate::schema::holidays;
+
+    let mut conn = state.pool.get().await?;
+
+    let holiday = holidays::table
+        .find(target_id as i32)
+        .first::<models::Holiday>(&mut conn)
+        .await?;
+
+    Ok(Json(holiday))
+}
+
+/// Creates a new holiday period
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Holiday),
+        (status = UNPROCESSABLE_ENTITY, description = "The holiday period ends before it starts")
+    )
+)]
+async fn create_holiday(
+    State(state): State<AppState>,
+    Json(payload): Json<models::NewHoliday>,
+) -> AppResult<Json<models::Holiday>> {
+    use crate::schema::holidays;
+
+    let mut conn = state.pool.get().await?;
+
+    let new_holiday = diesel::insert_into(holidays::table)
+        .values(&payload)
+        .returning(models::Holiday::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(new_holiday))
+}
+
+/// Updates a holiday period
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Holiday),
+        (status = NOT_FOUND, description = "The holiday period does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The holiday period ends before it starts")
+    ),
+    params(
+        ("id", description = "Id of the holiday period"),
+    )
+)]
+async fn update_holiday(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::UpdatedHoliday>,
+) -> AppResult<Json<models::Holiday>> {
+    use crate::schema::holidays::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let updated_holiday = diesel::update(holidays)
+        .filter(id.eq(target_id as i32))
+        .set(&payload)
+        .returning(models::Holiday::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(updated_holiday))
+}
+
+/// Deletes a holiday period
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, description = "The holiday period does not exist")
+    ),
+    params(
+        ("id", description = "Id of the holiday period"),
+    )
+)]
+async fn delete_holiday(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<()> {
+    use crate::schema::holidays::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let deleted_rows = diesel::delete(holidays.filter(id.eq(target_id as i32)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted_rows == 0 {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use super::history::Actor;
+use crate::{
+    errors::{not_found, unprocessable_entity, AppResult, BoxedAppError},
+    models, timetable, AppState,
+};
+
+const TAG: &str = "Homework templates";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_templates, create_template))
+        .routes(routes!(instantiate_template))
+        .routes(routes!(get_template, update_template, delete_template))
+}
+
+/// Retrieves all the templates
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::HomeworkTemplate])
+    )
+)]
+async fn list_templates(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<models::HomeworkTemplate>>> {
+    use crate::schema::homework_templates::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let results = homework_templates
+        .order_by((name, id))
+        .load::<models::HomeworkTemplate>(&mut conn)
+        .await?;
+
+    Ok(Json(results))
+}
+
+/// Retrieves a specific template
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::HomeworkTemplate),
+        (status = NOT_FOUND, description = "The template does not exist")
+    ),
+    params(
+        ("id", description = "Id of the template"),
+    )
+)]
+async fn get_template(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::HomeworkTemplate>> {
+    use crate::schema::homework_templates;
+
+    let mut conn = state.pool.get().await?;
+
+    let template = homework_templates::table
+        .find(target_id as i32)
+        .first::<models::HomeworkTemplate>(&mut conn)
+        .await?;
+
+    Ok(Json(template))
+}
+
+/// Creates a new template
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::HomeworkTemplate),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject does not exist or a field is invalid")
+    )
+)]
+async fn create_template(
+    State(state): State<AppState>,
+    Json(payload): Json<models::NewHomeworkTemplate>,
+) -> AppResult<Json<models::HomeworkTemplate>> {
+    use crate::schema::homework_templates;
+
+    let mut conn = state.pool.get().await?;
+
+    let new_template = diesel::insert_into(homework_templates::table)
+        .values(&payload)
+        .returning(models::HomeworkTemplate::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(new_template))
+}
+
+/// Updates a template
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::HomeworkTemplate),
+        (status = NOT_FOUND, description = "The template does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject does not exist or a field is invalid")
+    ),
+    params(
+        ("id", description = "Id of the template"),
+    )
+)]
+async fn update_template(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::UpdatedHomeworkTemplate>,
+) -> AppResult<Json<models::HomeworkTemplate>> {
+    use crate::schema::homework_templates::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let updated_template = diesel::update(homework_templates)
+        .filter(id.eq(target_id as i32))
+        .set(&payload)
+        .returning(models::HomeworkTemplate::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(updated_template))
+}
+
+/// Deletes a template
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, description = "The template does not exist")
+    ),
+    params(
+        ("id", description = "Id of the template"),
+    )
+)]
+async fn delete_template(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<()> {
+    use crate::schema::homework_templates::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let deleted_rows = diesel::delete(homework_templates.filter(id.eq(target_id as i32)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted_rows == 0 {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
+
+/// Replaces the placeholders of a template text, `None` if the chapter is
+/// needed but missing
+fn fill_placeholders(text: &str, date: chrono::NaiveDate, chapter: Option<i32>) -> Option<String> {
+    let text = text.replace("{date}", &date.format("%Y-%m-%d").to_string());
+
+    if !text.contains("{chapter}") {
+        return Some(text);
+    }
+
+    Some(text.replace("{chapter}", &chapter?.to_string()))
+}
+
+/// Creates a homework from a template
+#[utoipa::path(
+    post,
+    path = "/{id}/instantiate",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Homework),
+        (status = NOT_FOUND, description = "The template does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The template needs a chapter, or the subject does not exist or is in the trash")
+    ),
+    params(
+        ("id", description = "Id of the template"),
+    )
+)]
+async fn instantiate_template(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    actor: Actor,
+    Json(payload): Json<models::TemplateInstantiation>,
+) -> AppResult<Json<models::Homework>> {
+    use crate::schema::homework_templates;
+    use crate::schema::tags;
+
+    let mut conn = state.pool.get().await?;
+
+    let date = payload.date.unwrap_or_else(|| {
+        chrono::Utc::now()
+            .with_timezone(&state.timezone)
+            .date_naive()
+    });
+
+    let new_homework = conn
+        .transaction::<_, BoxedAppError, _>(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let template = homework_templates::table
+                    .find(target_id as i32)
+                    .first::<models::HomeworkTemplate>(conn)
+                    .await?;
+
+                // Tags may have been deleted since the template was saved
+                let tag_ids = tags::table
+                    .filter(tags::id.eq_any(&template.tag_ids))
+                    .select(tags::id)
+                    .load::<i32>(conn)
+                    .await?;
+
+                let due_date = payload.due_date.or_else(|| {
+                    let days = template.due_in_days?;
+                    let due_on = date.checked_add_signed(chrono::Duration::days(days.into()))?;
+
+                    timetable::localize(due_on.and_time(template.due_time), state.timezone)
+                });
+
+                let target_subject_id = payload.subject_id.or(template.subject_id);
+
+                super::homeworks::check_subject(conn, target_subject_id).await?;
+
+                let homework = models::NewHomework {
+                    due_date,
+                    title: fill_placeholders(&template.title, date, payload.chapter)
+                        .ok_or_else(unprocessable_entity)?,
+                    description: Some(
+                        fill_placeholders(&template.description, date, payload.chapter)
+                            .ok_or_else(unprocessable_entity)?,
+                    ),
+                    subject_id: target_subject_id,
+                    priority: Some(template.priority),
+                    status: None,
+                    estimated_minutes: template.estimated_minutes,
+                    difficulty: template.difficulty,
+                    exam_id: None,
+                    tag_ids: Some(tag_ids),
+                    due: None,
+                };
+
+                let new_homework = super::homeworks::insert_homework(conn, &homework).await?;
+
+                Ok(new_homework)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(new_homework))
+}
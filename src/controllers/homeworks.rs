@@ -1,15 +1,20 @@
+use std::collections::{HashMap, HashSet};
+
 use axum::{
     extract::{Path, Query, State},
     Json,
 };
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
 use serde::Deserialize;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
+use super::history::Actor;
 use crate::{
-    errors::{not_found, AppResult},
-    models, utils, AppState,
+    errors::{not_found, unprocessable_entity, AppResult, BoxedAppError},
+    models, quick_add, timetable, utils, AppState,
 };
 
 const TAG: &str = "Homeworks";
@@ -17,15 +22,21 @@ const TAG: &str = "Homeworks";
 pub fn router() -> OpenApiRouter<AppState> {
     OpenApiRouter::new()
         .routes(routes!(list_homeworks, create_homework))
+        .routes(routes!(bulk_update_homeworks))
+        .routes(routes!(quick_add_homework))
         .routes(routes!(get_homework, update_homework, delete_homework))
+        .routes(routes!(list_status_changes))
+        .routes(routes!(restore_homework))
+        .routes(routes!(duplicate_homework))
 }
 
-#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[derive(Debug, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
 struct ListHomeworksParams {
     /// Search query
     search: Option<String>,
 
-    /// Sort by
+    /// Sort by `due_date`, `priority`, `difficulty` or `estimated_minutes`,
+    /// prefixed by `-` for descending order
     sort: Option<String>,
 
     /// Only return homeworks due after `start_due_date`
@@ -37,26 +48,201 @@ struct ListHomeworksParams {
     /// Only return homeworks done or not done
     done: Option<bool>,
 
+    /// Filter by statuses
+    status: Option<utils::StatusSequence>,
+
     /// Filter by subjects
     subject_ids: Option<utils::IdSequence>,
+
+    /// Filter by tags
+    tag_ids: Option<utils::IdSequence>,
+
+    /// Whether homeworks must have any (default) or all of the `tag_ids`
+    tag_match: Option<TagMatch>,
+
+    /// Only return homeworks with at least this priority
+    min_priority: Option<models::Priority>,
+
+    /// Only return homeworks at least this difficult
+    min_difficulty: Option<i16>,
+
+    /// Only return homeworks at most this difficult
+    max_difficulty: Option<i16>,
+
+    /// Only return homeworks estimated to take at most `max_estimated_minutes`
+    max_estimated_minutes: Option<i32>,
+
+    /// Only return homeworks due during a term
+    term_id: Option<u32>,
+
+    /// Also return archived homeworks and the homeworks of archived subjects
+    include_archived: Option<bool>,
 }
 
-/// Retrieves all the homeworks
-#[utoipa::path(
-    get,
-    path = "/",
-    tag = TAG,
-    params(
-        ListHomeworksParams
-    ),
-    responses(
-        (status = OK, body = [models::HomeworkWithSubject])
-    )
-)]
-async fn list_homeworks(
-    State(state): State<AppState>,
-    Query(params): Query<ListHomeworksParams>,
-) -> AppResult<Json<Vec<models::HomeworkWithSubject>>> {
+#[derive(Debug, Default, Clone, Copy, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+struct BulkRequest {
+    /// Homeworks to change
+    ids: Option<Vec<i32>>,
+
+    /// Selects the homeworks to change like the listing parameters, narrowed
+    /// to `ids` if also set
+    filter: Option<ListHomeworksParams>,
+
+    operation: models::BulkOperation,
+}
+
+/// Attaches tags and tracked time to the results of a homeworks/subjects join
+pub(super) async fn with_details(
+    conn: &mut AsyncPgConnection,
+    results: Vec<(models::Homework, Option<models::Subject>)>,
+) -> QueryResult<Vec<models::HomeworkWithSubject>> {
+    use crate::schema::tags;
+    use crate::schema::time_entries;
+
+    let (homeworks, subjects): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+
+    let tags = models::HomeworkTag::belonging_to(&homeworks)
+        .inner_join(tags::table)
+        .select((models::HomeworkTag::as_select(), models::Tag::as_select()))
+        .order_by(tags::name)
+        .load::<(models::HomeworkTag, models::Tag)>(conn)
+        .await?
+        .grouped_by(&homeworks);
+
+    let tracked = time_entries::table
+        .filter(time_entries::homework_id.eq_any(homeworks.iter().map(|homework| homework.id)))
+        .group_by(time_entries::homework_id)
+        .select((
+            time_entries::homework_id,
+            super::time_entries::tracked_seconds(),
+        ))
+        .load::<(i32, i64)>(conn)
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let results = homeworks
+        .into_iter()
+        .zip(subjects)
+        .zip(tags)
+        .map(|((homework, subject), tags)| models::HomeworkWithSubject {
+            tracked_seconds: tracked.get(&homework.id).copied().unwrap_or_default(),
+            homework,
+            subject,
+            tags: tags.into_iter().map(|(_, tag)| tag).collect(),
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Replaces the tags of a homework
+async fn set_tags(
+    conn: &mut AsyncPgConnection,
+    target_id: i32,
+    tag_ids: &[i32],
+) -> QueryResult<()> {
+    use crate::schema::homework_tags;
+
+    diesel::delete(homework_tags::table.filter(homework_tags::homework_id.eq(target_id)))
+        .execute(conn)
+        .await?;
+
+    let rows = tag_ids
+        .iter()
+        .map(|&tag_id| models::HomeworkTag {
+            homework_id: target_id,
+            tag_id,
+        })
+        .collect::<Vec<_>>();
+
+    diesel::insert_into(homework_tags::table)
+        .values(&rows)
+        .on_conflict_do_nothing()
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Fills the due date of a new homework if it is to be resolved on the server
+pub(super) async fn resolve_due(
+    conn: &mut AsyncPgConnection,
+    state: &AppState,
+    payload: &mut models::NewHomework,
+) -> AppResult<()> {
+    if payload.due == Some(models::Due::NextLesson) {
+        let subject_id = payload.subject_id.ok_or_else(unprocessable_entity)?;
+
+        let lesson =
+            super::timetable::resolve_next_lesson(conn, state, subject_id, chrono::Utc::now())
+                .await?
+                .ok_or_else(unprocessable_entity)?;
+
+        payload.due_date = Some(lesson);
+    }
+
+    Ok(())
+}
+
+/// Rejects giving homeworks or exams a subject that does not exist or is in
+/// the trash
+pub(super) async fn check_subject(
+    conn: &mut AsyncPgConnection,
+    target_subject_id: Option<i32>,
+) -> AppResult<()> {
+    use crate::schema::subjects;
+
+    let Some(target_subject_id) = target_subject_id else {
+        return Ok(());
+    };
+
+    subjects::table
+        .find(target_subject_id)
+        .filter(subjects::deleted_at.is_null())
+        .select(subjects::id)
+        .first::<i32>(conn)
+        .await
+        .optional()?
+        .ok_or_else(unprocessable_entity)?;
+
+    Ok(())
+}
+
+/// Inserts a homework along with its tags
+pub(super) async fn insert_homework(
+    conn: &mut AsyncPgConnection,
+    payload: &models::NewHomework,
+) -> QueryResult<models::Homework> {
+    use crate::schema::homeworks;
+
+    let new_homework = diesel::insert_into(homeworks::table)
+        .values(payload)
+        .returning(models::Homework::as_returning())
+        .get_result(conn)
+        .await?;
+
+    if let Some(tag_ids) = &payload.tag_ids {
+        set_tags(conn, new_homework.id, tag_ids).await?;
+    }
+
+    Ok(new_homework)
+}
+
+/// Retrieves the homeworks matching the listing parameters
+async fn find_homeworks(
+    conn: &mut AsyncPgConnection,
+    state: &AppState,
+    params: ListHomeworksParams,
+) -> QueryResult<Vec<(models::Homework, Option<models::Subject>)>> {
     use crate::schema::homeworks::dsl::textsearchable_index_col;
     use crate::schema::homeworks::dsl::*;
     use crate::schema::subjects;
@@ -89,13 +275,19 @@ async fn list_homeworks(
     }
 
     if let Some(end_due_date) = params.end_due_date {
-        query = query.filter(due_date.ge(end_due_date));
+        query = query.filter(due_date.le(end_due_date));
     }
 
     if let Some(filter_done) = params.done {
         query = query.filter(done.eq(filter_done));
     }
 
+    if let Some(statuses) = params.status {
+        if !statuses.is_empty() {
+            query = query.filter(status.eq_any(statuses.statuses()));
+        }
+    }
+
     if let Some(subject_ids) = params.subject_ids {
         if !subject_ids.is_empty() {
             let ids = subject_ids.ids();
@@ -104,22 +296,109 @@ async fn list_homeworks(
         }
     }
 
-    if let Some(sort) = params.sort {
-        if sort == "due_date" {
-            query = query.order(due_date);
+    if let Some(tag_ids) = params.tag_ids {
+        if !tag_ids.is_empty() {
+            use crate::schema::homework_tags;
+
+            let ids = tag_ids.ids();
+            let tagged = homework_tags::table
+                .filter(homework_tags::tag_id.eq_any(ids.clone()))
+                .select(homework_tags::homework_id);
+
+            match params.tag_match.unwrap_or_default() {
+                TagMatch::Any => {
+                    query = query.filter(id.eq_any(tagged));
+                }
+                TagMatch::All => {
+                    let count = ids.iter().collect::<HashSet<_>>().len() as i64;
+
+                    query = query.filter(
+                        id.eq_any(
+                            tagged
+                                .group_by(homework_tags::homework_id)
+                                .having(diesel::dsl::count(homework_tags::tag_id).eq(count)),
+                        ),
+                    );
+                }
+            }
         }
     }
 
+    if let Some(min_priority) = params.min_priority {
+        query = query.filter(priority.ge(min_priority));
+    }
+
+    if let Some(min_difficulty) = params.min_difficulty {
+        query = query.filter(difficulty.ge(min_difficulty));
+    }
+
+    if let Some(max_difficulty) = params.max_difficulty {
+        query = query.filter(difficulty.le(max_difficulty));
+    }
+
+    if let Some(max_estimated_minutes) = params.max_estimated_minutes {
+        query = query.filter(estimated_minutes.le(max_estimated_minutes));
+    }
+
+    query = query.filter(deleted_at.is_null());
+
+    if !params.include_archived.unwrap_or_default() {
+        query = query
+            .filter(archived_at.is_null())
+            .filter(subjects::archived_at.is_null());
+    }
+
+    if let Some(term_id) = params.term_id {
+        let (term_start, term_end) = super::terms::term_range(conn, state, term_id).await?;
+
+        query = query.filter(due_date.ge(term_start).and(due_date.lt(term_end)));
+    }
+
+    if let Some(sort) = params.sort {
+        let (field, descending) = match sort.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (sort.as_str(), false),
+        };
+
+        query = match (field, descending) {
+            ("due_date", false) => query.order(due_date.asc().nulls_last()),
+            ("due_date", true) => query.order(due_date.desc().nulls_last()),
+            ("priority", false) => query.order((priority.asc(), due_date.asc().nulls_last())),
+            ("priority", true) => query.order((priority.desc(), due_date.asc().nulls_last())),
+            ("difficulty", false) => query.order(difficulty.asc().nulls_last()),
+            ("difficulty", true) => query.order(difficulty.desc().nulls_last()),
+            ("estimated_minutes", false) => query.order(estimated_minutes.asc().nulls_last()),
+            ("estimated_minutes", true) => query.order(estimated_minutes.desc().nulls_last()),
+            _ => query,
+        };
+    }
+
+    query
+        .load::<(models::Homework, Option<models::Subject>)>(conn)
+        .await
+}
+
+/// Retrieves all the homeworks
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    params(
+        ListHomeworksParams
+    ),
+    responses(
+        (status = OK, body = [models::HomeworkWithSubject])
+    )
+)]
+async fn list_homeworks(
+    State(state): State<AppState>,
+    Query(params): Query<ListHomeworksParams>,
+) -> AppResult<Json<Vec<models::HomeworkWithSubject>>> {
     let mut conn = state.pool.get().await?;
 
-    let results = query
-        .load::<(models::Homework, Option<models::Subject>)>(&mut conn)
-        .await?;
+    let results = find_homeworks(&mut conn, &state, params).await?;
 
-    let results = results
-        .into_iter()
-        .map(|(homework, subject)| models::HomeworkWithSubject { homework, subject })
-        .collect::<Vec<_>>();
+    let results = with_details(&mut conn, results).await?;
 
     Ok(Json(results))
 }
@@ -146,8 +425,9 @@ async fn get_homework(
 
     let mut conn = state.pool.get().await?;
 
-    let (homework, subject) = homeworks::table
+    let result = homeworks::table
         .find(target_id as i32)
+        .filter(homeworks::deleted_at.is_null())
         .left_join(subjects::table)
         .select((
             models::HOMEWORK_ALL_COLUMNS,
@@ -156,7 +436,12 @@ async fn get_homework(
         .first::<(models::Homework, Option<models::Subject>)>(&mut conn)
         .await?;
 
-    Ok(Json(models::HomeworkWithSubject { homework, subject }))
+    let result = with_details(&mut conn, vec![result])
+        .await?
+        .pop()
+        .ok_or_else(not_found)?;
+
+    Ok(Json(result))
 }
 
 /// Creates a new homework
@@ -165,26 +450,208 @@ async fn get_homework(
     path = "/",
     tag = TAG,
     responses(
-        (status = OK, body = models::Homework)
+        (status = OK, body = models::Homework),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject or a tag does not exist, or the due date cannot be resolved")
     )
 )]
 async fn create_homework(
     State(state): State<AppState>,
-    Json(payload): Json<models::NewHomework>,
+    actor: Actor,
+    Json(mut payload): Json<models::NewHomework>,
 ) -> AppResult<Json<models::Homework>> {
-    use crate::schema::homeworks;
-
     let mut conn = state.pool.get().await?;
 
-    let new_homework = diesel::insert_into(homeworks::table)
-        .values(&payload)
-        .returning(models::Homework::as_returning())
-        .get_result(&mut conn)
+    check_subject(&mut conn, payload.subject_id).await?;
+    resolve_due(&mut conn, &state, &mut payload).await?;
+
+    let new_homework = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                insert_homework(conn, &payload).await
+            }
+            .scope_boxed()
+        })
         .await?;
 
     Ok(Json(new_homework))
 }
 
+/// Parses free text into a homework, and creates it unless `dry_run` is set
+///
+/// The text may mention a subject, a due date such as `friday 8am`, `demain`
+/// or `in 3 days`, and `#tags`. Without a time, the homework is due at the
+/// start of the lesson of the subject on that day.
+#[utoipa::path(
+    post,
+    path = "/quick",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::QuickAddResult),
+        (status = UNPROCESSABLE_ENTITY, description = "The text has no title")
+    )
+)]
+async fn quick_add_homework(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(payload): Json<models::QuickAdd>,
+) -> AppResult<Json<models::QuickAddResult>> {
+    use crate::schema::subjects;
+    use crate::schema::tags;
+    use crate::schema::timetable_slots;
+
+    let mut conn = state.pool.get().await?;
+
+    let now = chrono::Utc::now().with_timezone(&state.timezone);
+    let today = now.date_naive();
+
+    let candidates = subjects::table
+        .filter(subjects::deleted_at.is_null())
+        .filter(subjects::archived_at.is_null())
+        .select(models::Subject::as_select())
+        .order_by(subjects::id)
+        .load::<models::Subject>(&mut conn)
+        .await?;
+
+    let parsed = quick_add::parse(&payload.text, &candidates, today);
+
+    let subject = candidates
+        .into_iter()
+        .find(|subject| Some(subject.id) == parsed.subject_id);
+
+    // A time alone stands for its next occurrence
+    let due_on = parsed.due_on.or_else(|| {
+        let time = parsed.due_at?;
+
+        Some(if time > now.time() {
+            today
+        } else {
+            today + chrono::Duration::days(1)
+        })
+    });
+
+    let mut due_date = None;
+
+    if let Some(due_on) = due_on {
+        let lesson = match (&subject, parsed.due_at) {
+            (Some(subject), None) => timetable_slots::table
+                .filter(timetable_slots::subject_id.eq(subject.id))
+                .load::<models::TimetableSlot>(&mut conn)
+                .await?
+                .iter()
+                .filter(|slot| timetable::occurs_on(slot, due_on, state.timetable_a_week))
+                .map(|slot| slot.starts_at)
+                .min(),
+            _ => None,
+        };
+
+        let due_at = parsed
+            .due_at
+            .or(lesson)
+            .unwrap_or(quick_add::DEFAULT_DUE_TIME);
+
+        due_date = timetable::localize(due_on.and_time(due_at), state.timezone);
+    }
+
+    let mut names = parsed.tags;
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.to_lowercase()));
+
+    let (tags, new_tags): (Vec<_>, Vec<_>) = {
+        let existing = tags::table
+            .order_by(tags::id)
+            .load::<models::Tag>(&mut conn)
+            .await?
+            .into_iter()
+            .filter(|tag| {
+                names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&tag.name))
+            })
+            .collect::<Vec<_>>();
+
+        let new_tags = names
+            .into_iter()
+            .filter(|name| {
+                !existing
+                    .iter()
+                    .any(|tag| name.eq_ignore_ascii_case(&tag.name))
+            })
+            .collect();
+
+        (existing, new_tags)
+    };
+
+    let preview = models::QuickAddPreview {
+        title: parsed.title,
+        subject,
+        due_date,
+        tags,
+        new_tags,
+    };
+
+    if payload.dry_run.unwrap_or(false) {
+        return Ok(Json(models::QuickAddResult {
+            preview,
+            homework: None,
+        }));
+    }
+
+    if preview.title.is_empty() {
+        return Err(unprocessable_entity());
+    }
+
+    let rows = preview
+        .new_tags
+        .iter()
+        .map(|name| models::NewTag {
+            name: name.clone(),
+            hex_color: None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut homework = models::NewHomework {
+        due_date: preview.due_date,
+        title: preview.title.clone(),
+        description: None,
+        subject_id: preview.subject.as_ref().map(|subject| subject.id),
+        priority: None,
+        status: None,
+        estimated_minutes: None,
+        difficulty: None,
+        exam_id: None,
+        tag_ids: Some(preview.tags.iter().map(|tag| tag.id).collect()),
+        due: None,
+    };
+
+    let new_homework = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                if !rows.is_empty() {
+                    let created = diesel::insert_into(tags::table)
+                        .values(&rows)
+                        .returning(tags::id)
+                        .get_results::<i32>(conn)
+                        .await?;
+
+                    homework.tag_ids.get_or_insert_default().extend(created);
+                }
+
+                insert_homework(conn, &homework).await
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(models::QuickAddResult {
+        preview,
+        homework: Some(new_homework),
+    }))
+}
+
 /// Updates a homework
 #[utoipa::path(
     put,
@@ -192,7 +659,8 @@ async fn create_homework(
     tag = TAG,
     responses(
         (status = OK, body = models::Homework),
-        (status = NOT_FOUND, description = "The homework does not exist")
+        (status = NOT_FOUND, description = "The homework does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject or a tag does not exist")
     ),
     params(
         ("id", description = "Id of the homework"),
@@ -201,23 +669,59 @@ async fn create_homework(
 async fn update_homework(
     State(state): State<AppState>,
     Path(target_id): Path<u32>,
-    Json(payload): Json<models::UpdatedHomework>,
+    actor: Actor,
+    Json(mut payload): Json<models::UpdatedHomework>,
 ) -> AppResult<Json<models::Homework>> {
     use crate::schema::homeworks;
 
     let mut conn = state.pool.get().await?;
 
-    let updated_homework = diesel::update(homeworks::table)
-        .filter(homeworks::id.eq(target_id as i32))
-        .set(&payload)
-        .returning(models::Homework::as_returning())
-        .get_result(&mut conn)
+    check_subject(&mut conn, payload.changes.subject_id).await?;
+
+    let updated_homework = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                if let (None, Some(done)) = (payload.changes.status, payload.done) {
+                    let currently_done = homeworks::table
+                        .find(target_id as i32)
+                        .filter(homeworks::deleted_at.is_null())
+                        .select(homeworks::done)
+                        .first::<bool>(conn)
+                        .await?;
+
+                    if currently_done != done {
+                        payload.changes.status = Some(if done {
+                            models::HomeworkStatus::Done
+                        } else {
+                            models::HomeworkStatus::Todo
+                        });
+                    }
+                }
+
+                let updated_homework = diesel::update(homeworks::table)
+                    .filter(homeworks::id.eq(target_id as i32))
+                    .filter(homeworks::deleted_at.is_null())
+                    .set((&payload.changes, homeworks::updated_at.eq(diesel::dsl::now)))
+                    .returning(models::Homework::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                if let Some(tag_ids) = &payload.tag_ids {
+                    set_tags(conn, updated_homework.id, tag_ids).await?;
+                }
+
+                QueryResult::Ok(updated_homework)
+            }
+            .scope_boxed()
+        })
         .await?;
 
     Ok(Json(updated_homework))
 }
 
-/// Deletes a homework
+/// Moves a homework to the trash, stopping its timer if running
 #[utoipa::path(
     delete,
     path = "/{id}",
@@ -233,13 +737,30 @@ async fn update_homework(
 async fn delete_homework(
     State(state): State<AppState>,
     Path(target_id): Path<u32>,
+    actor: Actor,
 ) -> AppResult<()> {
     use crate::schema::homeworks::dsl::*;
 
     let mut conn = state.pool.get().await?;
 
-    let deleted_rows = diesel::delete(homeworks.filter(id.eq(target_id as i32)))
-        .execute(&mut conn)
+    let deleted_rows = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let deleted_rows = diesel::update(homeworks)
+                    .filter(id.eq(target_id as i32))
+                    .filter(deleted_at.is_null())
+                    .set(deleted_at.eq(diesel::dsl::now))
+                    .execute(conn)
+                    .await?;
+
+                super::time_entries::stop_timers(conn, &[target_id as i32]).await?;
+
+                QueryResult::Ok(deleted_rows)
+            }
+            .scope_boxed()
+        })
         .await?;
 
     if deleted_rows == 0 {
@@ -248,3 +769,314 @@ async fn delete_homework(
 
     Ok(())
 }
+
+/// Retrieves the status history of a homework
+#[utoipa::path(
+    get,
+    path = "/{id}/status-history",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::HomeworkStatusChange]),
+        (status = NOT_FOUND, description = "The homework does not exist")
+    ),
+    params(
+        ("id", description = "Id of the homework"),
+    )
+)]
+async fn list_status_changes(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<Vec<models::HomeworkStatusChange>>> {
+    use crate::schema::homework_status_changes;
+    use crate::schema::homeworks;
+
+    let mut conn = state.pool.get().await?;
+
+    let homework = homeworks::table
+        .find(target_id as i32)
+        .filter(homeworks::deleted_at.is_null())
+        .select(models::HOMEWORK_ALL_COLUMNS)
+        .first::<models::Homework>(&mut conn)
+        .await?;
+
+    let results = models::HomeworkStatusChange::belonging_to(&homework)
+        .order_by((
+            homework_status_changes::changed_at,
+            homework_status_changes::id,
+        ))
+        .load::<models::HomeworkStatusChange>(&mut conn)
+        .await?;
+
+    Ok(Json(results))
+}
+
+/// Restores a homework from the trash
+#[utoipa::path(
+    post,
+    path = "/{id}/restore",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Homework),
+        (status = NOT_FOUND, description = "The homework is not in the trash")
+    ),
+    params(
+        ("id", description = "Id of the homework"),
+    )
+)]
+async fn restore_homework(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    actor: Actor,
+) -> AppResult<Json<models::Homework>> {
+    use crate::schema::homeworks::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let restored_homework = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                diesel::update(homeworks)
+                    .filter(id.eq(target_id as i32))
+                    .filter(deleted_at.is_not_null())
+                    .set(deleted_at.eq(None::<chrono::DateTime<chrono::Utc>>))
+                    .returning(models::Homework::as_returning())
+                    .get_result(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(restored_homework))
+}
+
+/// Applies an operation to many homeworks at once
+///
+/// Either every homework is changed or none is. Homeworks that do not exist
+/// or are in the trash are reported as not found.
+#[utoipa::path(
+    post,
+    path = "/bulk",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::BulkResult]),
+        (status = UNPROCESSABLE_ENTITY, description = "Neither ids nor a filter were given, or the subject does not exist")
+    )
+)]
+async fn bulk_update_homeworks(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(payload): Json<BulkRequest>,
+) -> AppResult<Json<Vec<models::BulkResult>>> {
+    use crate::schema::homeworks::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    if let models::BulkOperation::MoveToSubject {
+        subject_id: target_subject_id,
+    } = payload.operation
+    {
+        check_subject(&mut conn, target_subject_id).await?;
+    }
+
+    if payload.ids.is_none() && payload.filter.is_none() {
+        return Err(unprocessable_entity());
+    }
+
+    let state = &state;
+
+    let results = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let target_ids = match (payload.ids, payload.filter) {
+                    (ids, None) => ids.unwrap_or_default(),
+                    (ids, Some(filter)) => find_homeworks(conn, state, filter)
+                        .await?
+                        .into_iter()
+                        .map(|(homework, _)| homework.id)
+                        .filter(|homework_id| {
+                            ids.as_ref().is_none_or(|ids| ids.contains(homework_id))
+                        })
+                        .collect(),
+                };
+
+                let targets = homeworks
+                    .filter(id.eq_any(&target_ids))
+                    .filter(deleted_at.is_null());
+
+                // Locked until the end of the transaction, for the homeworks
+                // not to change in between
+                let found = targets
+                    .clone()
+                    .select(id)
+                    .for_update()
+                    .load::<i32>(conn)
+                    .await?
+                    .into_iter()
+                    .collect::<HashSet<_>>();
+
+                let updated = match payload.operation {
+                    models::BulkOperation::SetDone { done: target_done } => {
+                        let target_status = if target_done {
+                            models::HomeworkStatus::Done
+                        } else {
+                            models::HomeworkStatus::Todo
+                        };
+
+                        diesel::update(targets.filter(done.ne(target_done)))
+                            .set((status.eq(target_status), updated_at.eq(diesel::dsl::now)))
+                            .returning(models::Homework::as_returning())
+                            .get_results(conn)
+                            .await?
+                    }
+                    models::BulkOperation::MoveToSubject {
+                        subject_id: target_subject_id,
+                    } => {
+                        diesel::update(
+                            targets.filter(subject_id.is_distinct_from(target_subject_id)),
+                        )
+                        .set((
+                            subject_id.eq(target_subject_id),
+                            updated_at.eq(diesel::dsl::now),
+                        ))
+                        .returning(models::Homework::as_returning())
+                        .get_results(conn)
+                        .await?
+                    }
+                    models::BulkOperation::ShiftDueDate { days } => {
+                        let shift = diesel::data_types::PgInterval::from_days(days);
+
+                        diesel::update(targets.filter(due_date.is_not_null()))
+                            .set((
+                                due_date.eq(due_date + shift),
+                                updated_at.eq(diesel::dsl::now),
+                            ))
+                            .returning(models::Homework::as_returning())
+                            .get_results(conn)
+                            .await?
+                    }
+                    models::BulkOperation::Delete => {
+                        let deleted = diesel::update(targets)
+                            .set(deleted_at.eq(diesel::dsl::now))
+                            .returning(models::Homework::as_returning())
+                            .get_results(conn)
+                            .await?;
+
+                        let deleted_ids = deleted
+                            .iter()
+                            .map(|homework| homework.id)
+                            .collect::<Vec<_>>();
+
+                        super::time_entries::stop_timers(conn, &deleted_ids).await?;
+
+                        deleted
+                    }
+                };
+
+                let mut updated = updated
+                    .into_iter()
+                    .map(|homework| (homework.id, homework))
+                    .collect::<HashMap<_, _>>();
+
+                let results = target_ids
+                    .iter()
+                    .map(|&target_id| {
+                        let homework = updated.remove(&target_id);
+
+                        let result_status = match homework {
+                            Some(_) => models::BulkStatus::Updated,
+                            None if found.contains(&target_id) => models::BulkStatus::Unchanged,
+                            None => models::BulkStatus::NotFound,
+                        };
+
+                        models::BulkResult {
+                            id: target_id,
+                            status: result_status,
+                            homework,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                QueryResult::Ok(results)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(results))
+}
+
+/// Creates a copy of a homework, with the same tags
+///
+/// The copy starts over with the `todo` status, and is not a revision of the
+/// exam of the original, if any.
+#[utoipa::path(
+    post,
+    path = "/{id}/duplicate",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Homework),
+        (status = NOT_FOUND, description = "The homework does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject of the homework is in the trash")
+    ),
+    params(
+        ("id", description = "Id of the homework to copy"),
+    )
+)]
+async fn duplicate_homework(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    actor: Actor,
+) -> AppResult<Json<models::Homework>> {
+    use crate::schema::homework_tags;
+    use crate::schema::homeworks;
+
+    let mut conn = state.pool.get().await?;
+
+    let new_homework = conn
+        .transaction::<_, BoxedAppError, _>(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let homework = homeworks::table
+                    .find(target_id as i32)
+                    .filter(homeworks::deleted_at.is_null())
+                    .select(models::HOMEWORK_ALL_COLUMNS)
+                    .first::<models::Homework>(conn)
+                    .await?;
+
+                check_subject(conn, homework.subject_id).await?;
+
+                let tag_ids = homework_tags::table
+                    .filter(homework_tags::homework_id.eq(homework.id))
+                    .select(homework_tags::tag_id)
+                    .load::<i32>(conn)
+                    .await?;
+
+                let copy = models::NewHomework {
+                    due_date: homework.due_date,
+                    title: homework.title,
+                    description: Some(homework.description),
+                    subject_id: homework.subject_id,
+                    priority: Some(homework.priority),
+                    status: None,
+                    estimated_minutes: homework.estimated_minutes,
+                    difficulty: homework.difficulty,
+                    // A revision of an exam would be replaced with the others
+                    exam_id: None,
+                    tag_ids: Some(tag_ids),
+                    due: None,
+                };
+
+                Ok(insert_homework(conn, &copy).await?)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(new_homework))
+}
@@ -1,10 +1,14 @@
+use std::fmt::Write;
+
 use axum::{extract::State, http::header, response::IntoResponse};
+use chrono::{Datelike, Offset, TimeZone};
+use chrono_tz::{OffsetComponents, OffsetName};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
-use icalendar::{Calendar, Component, Event, EventLike};
+use icalendar::{Calendar, CalendarDateTime, Component, Event, EventLike, Property};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
-use crate::{errors::AppResult, models, AppState};
+use crate::{errors::AppResult, models, timetable, AppState};
 
 const TAG: &str = "Homeworks";
 
@@ -34,13 +38,13 @@ async fn generate_icalendar(State(state): State<AppState>) -> AppResult<impl Int
             Option::<models::Subject>::as_select(),
         ))
         .filter(due_date.is_not_null())
+        .filter(deleted_at.is_null())
+        .filter(archived_at.is_null())
+        .filter(subjects::archived_at.is_null())
         .load::<(models::Homework, Option<models::Subject>)>(&mut conn)
         .await?;
 
-    let results = results
-        .into_iter()
-        .map(|(homework, subject)| models::HomeworkWithSubject { homework, subject })
-        .collect::<Vec<_>>();
+    let results = super::homeworks::with_details(&mut conn, results).await?;
 
     for res in results {
         let due_date_val = res.homework.due_date.expect("no due date");
@@ -52,19 +56,269 @@ async fn generate_icalendar(State(state): State<AppState>) -> AppResult<impl Int
             summary.push_str(&subject.name);
         }
 
-        let event = Event::new()
+        let mut event = Event::new();
+
+        event
             .summary(&summary)
             .description(&res.homework.description)
             .starts(due_date_val)
             .ends(due_date_val + chrono::Duration::hours(1))
-            .done();
+            .priority(ical_priority(res.homework.priority));
+
+        // One property per tag, the values being escaped as TEXT by icalendar
+        for tag in &res.tags {
+            event.append_multi_property(Property::new("CATEGORIES", &tag.name));
+        }
+
+        let event = event.done();
 
         calendar = calendar.push(event);
     }
 
+    let exams = crate::schema::exams::table
+        .left_join(subjects::table)
+        .filter(subjects::deleted_at.is_null())
+        .filter(subjects::archived_at.is_null())
+        .select((
+            models::Exam::as_select(),
+            Option::<models::Subject>::as_select(),
+        ))
+        .load::<(models::Exam, Option<models::Subject>)>(&mut conn)
+        .await?;
+
+    for (exam, subject) in exams {
+        let mut summary = format!("Exam: {}", exam.title);
+
+        if let Some(subject) = subject {
+            summary.push_str(" - ");
+            summary.push_str(&subject.name);
+        }
+
+        let mut event = Event::new();
+
+        event
+            .summary(&summary)
+            .starts(exam.starts_at)
+            .ends(exam.starts_at + chrono::Duration::minutes(exam.duration_minutes.into()));
+
+        if !exam.chapters.is_empty() {
+            event.description(&format!("Chapters: {}", exam.chapters.join(", ")));
+        }
+
+        if let Some(room) = &exam.room {
+            event.location(room);
+        }
+
+        calendar = calendar.push(event.done());
+    }
+
+    let slots = crate::schema::timetable_slots::table
+        .inner_join(subjects::table)
+        .filter(subjects::deleted_at.is_null())
+        .filter(subjects::archived_at.is_null())
+        .select((
+            models::TimetableSlot::as_select(),
+            models::Subject::as_select(),
+        ))
+        .load::<(models::TimetableSlot, models::Subject)>(&mut conn)
+        .await?;
+
+    let this_week = timetable::week_start(
+        chrono::Utc::now()
+            .with_timezone(&state.timezone)
+            .date_naive(),
+    );
+
+    let holidays = crate::schema::holidays::table
+        .filter(crate::schema::holidays::ends_on.ge(this_week))
+        .load::<models::Holiday>(&mut conn)
+        .await?;
+
+    let has_lessons = !slots.is_empty();
+
+    for (slot, subject) in slots {
+        let Some(date) = timetable::first_occurrence(&slot, this_week, state.timetable_a_week)
+        else {
+            continue;
+        };
+
+        let local_time = |time| CalendarDateTime::WithTimezone {
+            date_time: date.and_time(time),
+            tzid: state.timezone.name().to_string(),
+        };
+
+        let rrule = match slot.rotation {
+            models::WeekRotation::Every => "FREQ=WEEKLY",
+            models::WeekRotation::A | models::WeekRotation::B => "FREQ=WEEKLY;INTERVAL=2",
+        };
+
+        let mut event = Event::new();
+
+        event
+            .summary(&subject.name)
+            .starts(local_time(slot.starts_at))
+            .ends(local_time(slot.ends_at))
+            .add_property("RRULE", rrule);
+
+        if let Some(room) = &slot.room {
+            event.location(room);
+        }
+
+        // Lessons do not take place during holidays
+        for holiday in &holidays {
+            let cancelled = holiday
+                .starts_on
+                .max(date)
+                .iter_days()
+                .take_while(|day| *day <= holiday.ends_on)
+                .filter(|day| timetable::occurs_on(&slot, *day, state.timetable_a_week));
+
+            for day in cancelled {
+                let exdate = day.and_time(slot.starts_at).format("%Y%m%dT%H%M%S");
+
+                event.append_multi_property(
+                    Property::new("EXDATE", exdate.to_string())
+                        .add_parameter("TZID", state.timezone.name())
+                        .done(),
+                );
+            }
+        }
+
+        calendar = calendar.push(event.done());
+    }
+
     let calendar = calendar.done();
 
-    let res = calendar.to_string();
+    let mut res = calendar.to_string();
+
+    // Lessons refer to the time zone by TZID, which must be defined
+    if has_lessons {
+        let end = res.rfind("END:VCALENDAR").unwrap_or(res.len());
+        res.insert_str(end, &vtimezone(state.timezone, this_week));
+    }
 
     Ok(([(header::CONTENT_TYPE, "text/calendar")], res))
 }
+
+/// Maps a homework priority to the iCalendar `PRIORITY` scale (1 is highest)
+fn ical_priority(priority: models::Priority) -> u32 {
+    match priority {
+        models::Priority::Urgent => 1,
+        models::Priority::High => 3,
+        models::Priority::Normal => 5,
+        models::Priority::Low => 9,
+    }
+}
+
+/// Defines `timezone` as a `VTIMEZONE` component, by the transitions it went
+/// through in the year before `until`
+fn vtimezone(timezone: chrono_tz::Tz, until: chrono::NaiveDate) -> String {
+    let end = until.and_time(chrono::NaiveTime::MIN).and_utc();
+    let mut at = end - chrono::Duration::days(366);
+    let mut offset = timezone.offset_from_utc_datetime(&at.naive_utc());
+
+    let mut standard = None;
+    let mut daylight = None;
+
+    // Offsets change on quarter hours
+    while at < end {
+        at += chrono::Duration::minutes(15);
+
+        let next = timezone.offset_from_utc_datetime(&at.naive_utc());
+
+        if next.fix() != offset.fix() {
+            let transition = Some((at, offset, next));
+
+            if next.dst_offset().is_zero() {
+                standard = transition;
+            } else {
+                daylight = transition;
+            }
+        }
+
+        offset = next;
+    }
+
+    let yearly = standard.is_some() && daylight.is_some();
+
+    let mut out = String::new();
+    let _ = write!(out, "BEGIN:VTIMEZONE\r\nTZID:{}\r\n", timezone.name());
+
+    if standard.is_none() && daylight.is_none() {
+        let _ = write!(
+            out,
+            "BEGIN:STANDARD\r\nDTSTART:19700101T000000\r\nTZOFFSETFROM:{offset}\r\n\
+             TZOFFSETTO:{offset}\r\n",
+            offset = utc_offset(offset.fix()),
+        );
+
+        if let Some(name) = offset.abbreviation() {
+            let _ = write!(out, "TZNAME:{name}\r\n");
+        }
+
+        out.push_str("END:STANDARD\r\n");
+    }
+
+    for (kind, transition) in [("STANDARD", standard), ("DAYLIGHT", daylight)] {
+        let Some((at, from, to)) = transition else {
+            continue;
+        };
+
+        // Local time in the offset being left, as the transition is defined
+        let start = at.naive_utc() + chrono::Duration::seconds(from.fix().local_minus_utc().into());
+
+        let _ = write!(
+            out,
+            "BEGIN:{kind}\r\nDTSTART:{}\r\n",
+            start.format("%Y%m%dT%H%M%S")
+        );
+
+        if yearly {
+            let _ = write!(
+                out,
+                "RRULE:FREQ=YEARLY;BYMONTH={};BYDAY={}\r\n",
+                start.month(),
+                yearly_weekday(start.date()),
+            );
+        }
+
+        let _ = write!(
+            out,
+            "TZOFFSETFROM:{}\r\nTZOFFSETTO:{}\r\n",
+            utc_offset(from.fix()),
+            utc_offset(to.fix()),
+        );
+
+        if let Some(name) = to.abbreviation() {
+            let _ = write!(out, "TZNAME:{name}\r\n");
+        }
+
+        let _ = write!(out, "END:{kind}\r\n");
+    }
+
+    out.push_str("END:VTIMEZONE\r\n");
+
+    out
+}
+
+/// Formats an offset from UTC as in `TZOFFSETFROM`, such as `+0130`
+fn utc_offset(offset: chrono::FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.unsigned_abs() / 60;
+
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+/// `BYDAY` value of a yearly rule falling on `date`, such as `-1SU` for the
+/// last Sunday of its month
+fn yearly_weekday(date: chrono::NaiveDate) -> String {
+    let weekday =
+        ["MO", "TU", "WE", "TH", "FR", "SA", "SU"][date.weekday().num_days_from_monday() as usize];
+
+    if (date + chrono::Duration::days(7)).month() != date.month() {
+        format!("-1{weekday}")
+    } else {
+        format!("{}{weekday}", (date.day() - 1) / 7 + 1)
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{DefaultBodyLimit, Query, State},
+    http::StatusCode,
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use serde::Deserialize;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use super::history::Actor;
+use crate::{
+    backup,
+    errors::{unprocessable_entity, AppResult, BoxedAppError},
+    models, quick_add, timetable, AppState,
+};
+
+const TAG: &str = "Export";
+
+/// Largest dump accepted, in bytes
+const MAX_DUMP_SIZE: usize = 64 * 1024 * 1024;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(import_json))
+        .routes(routes!(import_homeworks_csv))
+        .layer(DefaultBodyLimit::max(MAX_DUMP_SIZE))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct ImportParams {
+    /// Whether to merge the dump with the existing data (default) or to
+    /// replace the data with it
+    mode: Option<models::ImportMode>,
+}
+
+/// Imports a JSON dump made by the export
+///
+/// Records get new ids but keep their timestamps. A timer of the dump still
+/// running is stopped at the time of the dump if another one already is.
+/// Nothing is imported if any record fails to.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = TAG,
+    params(
+        ImportParams
+    ),
+    responses(
+        (status = OK, body = models::ImportSummary),
+        (status = UNPROCESSABLE_ENTITY, description = "The dump has another schema version or is inconsistent")
+    )
+)]
+async fn import_json(
+    State(state): State<AppState>,
+    Query(params): Query<ImportParams>,
+    actor: Actor,
+    Json(payload): Json<models::Export>,
+) -> AppResult<Json<models::ImportSummary>> {
+    let mut conn = state.pool.get().await?;
+
+    let summary = conn
+        .transaction::<_, BoxedAppError, _>(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                backup::restore(conn, payload, params.mode.unwrap_or_default()).await
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(summary))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct CsvImportParams {
+    /// Column holding the titles
+    title: String,
+
+    /// Column holding the descriptions
+    description: Option<String>,
+
+    /// Column holding the due dates
+    due_date: Option<String>,
+
+    /// `strftime` format of the due dates, which may have no time of the day,
+    /// RFC 3339 or `%Y-%m-%d` by default
+    due_date_format: Option<String>,
+
+    /// Column holding the names of the subjects
+    subject: Option<String>,
+
+    /// Separator of the columns, `,` by default
+    delimiter: Option<char>,
+
+    /// Only validate the rows, without creating anything
+    dry_run: Option<bool>,
+}
+
+/// Parses a due date as given by the file, local dates and times being in
+/// `timezone`
+fn parse_due_date(
+    value: &str,
+    format: Option<&str>,
+    timezone: chrono_tz::Tz,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let local = match format {
+        Some(format) => chrono::NaiveDateTime::parse_from_str(value, format).or_else(|_| {
+            chrono::NaiveDate::parse_from_str(value, format)
+                .map(|date| date.and_time(quick_add::DEFAULT_DUE_TIME))
+        }),
+        None => {
+            if let Ok(date_time) = chrono::DateTime::parse_from_rfc3339(value) {
+                return Some(date_time.to_utc());
+            }
+
+            chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|date| date.and_time(quick_add::DEFAULT_DUE_TIME))
+        }
+    };
+
+    timetable::localize(local.ok()?, timezone)
+}
+
+/// Reads the rows of a CSV file according to the column mapping
+fn read_csv(
+    body: &str,
+    params: &CsvImportParams,
+    timezone: chrono_tz::Tz,
+) -> AppResult<Vec<models::CsvImportRow>> {
+    let delimiter = params.delimiter.unwrap_or(',');
+
+    if !delimiter.is_ascii() {
+        return Err(unprocessable_entity());
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .from_reader(body.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|_| unprocessable_entity())?
+        .clone();
+
+    // Every mapped column must be in the file
+    let column = |name: Option<&str>| {
+        name.map(|name| {
+            headers
+                .iter()
+                .position(|header| header.trim() == name)
+                .ok_or_else(unprocessable_entity)
+        })
+        .transpose()
+    };
+
+    let title = column(Some(&params.title))?;
+    let description = column(params.description.as_deref())?;
+    let due_date = column(params.due_date.as_deref())?;
+    let subject = column(params.subject.as_deref())?;
+
+    let rows = reader
+        .records()
+        .map(|record| {
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => {
+                    return models::CsvImportRow {
+                        line: err.position().map_or(0, |position| position.line()),
+                        title: String::new(),
+                        description: String::new(),
+                        due_date: None,
+                        subject: None,
+                        errors: vec![format!("malformed row: {err}")],
+                        homework_id: None,
+                    }
+                }
+            };
+
+            let field = |index: Option<usize>| {
+                index
+                    .and_then(|index| record.get(index))
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+            };
+
+            let mut errors = Vec::new();
+
+            let title = field(title).unwrap_or_default().to_owned();
+
+            if title.is_empty() {
+                errors.push("missing title".to_owned());
+            }
+
+            let due_date = field(due_date).and_then(|value| {
+                let due_date = parse_due_date(value, params.due_date_format.as_deref(), timezone);
+
+                if due_date.is_none() {
+                    errors.push(format!("invalid due date: {value}"));
+                }
+
+                due_date
+            });
+
+            models::CsvImportRow {
+                line: record.position().map_or(0, |position| position.line()),
+                title,
+                description: field(description).unwrap_or_default().to_owned(),
+                due_date,
+                subject: field(subject).map(str::to_owned),
+                errors,
+                homework_id: None,
+            }
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+/// Imports homeworks from a CSV file, such as a list of assignments
+///
+/// Columns are mapped to homework fields by name. Subjects are matched by
+/// name, regardless of case, and created if missing. Nothing is imported if
+/// any row is invalid, the response then listing the errors of each row.
+#[utoipa::path(
+    post,
+    path = "/homeworks.csv",
+    tag = TAG,
+    params(
+        CsvImportParams
+    ),
+    request_body(content = String, content_type = "text/csv"),
+    responses(
+        (status = OK, body = models::CsvImportResult),
+        (status = UNPROCESSABLE_ENTITY, body = models::CsvImportResult, description = "Some rows are invalid, or a mapped column is not in the file")
+    )
+)]
+async fn import_homeworks_csv(
+    State(state): State<AppState>,
+    Query(params): Query<CsvImportParams>,
+    actor: Actor,
+    body: String,
+) -> AppResult<(StatusCode, Json<models::CsvImportResult>)> {
+    use crate::schema::subjects;
+
+    let mut rows = read_csv(&body, &params, state.timezone)?;
+
+    let mut conn = state.pool.get().await?;
+
+    let mut subject_ids = subjects::table
+        .filter(subjects::deleted_at.is_null())
+        .select((subjects::name, subjects::id))
+        .load::<(String, i32)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(name, id)| (name.to_lowercase(), id))
+        .collect::<HashMap<_, _>>();
+
+    let mut new_subjects = Vec::<String>::new();
+
+    for name in rows.iter().filter_map(|row| row.subject.as_ref()) {
+        let key = name.to_lowercase();
+
+        if !subject_ids.contains_key(&key)
+            && !new_subjects.iter().any(|new| new.to_lowercase() == key)
+        {
+            new_subjects.push(name.clone());
+        }
+    }
+
+    let valid = rows.iter().all(|row| row.errors.is_empty());
+
+    if !valid || params.dry_run.unwrap_or(false) {
+        let status = if valid {
+            StatusCode::OK
+        } else {
+            StatusCode::UNPROCESSABLE_ENTITY
+        };
+
+        return Ok((status, Json(models::CsvImportResult { rows, new_subjects })));
+    }
+
+    let (rows, new_subjects) = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                for name in &new_subjects {
+                    let new_id = diesel::insert_into(subjects::table)
+                        .values(subjects::name.eq(name))
+                        .returning(subjects::id)
+                        .get_result::<i32>(conn)
+                        .await?;
+
+                    subject_ids.insert(name.to_lowercase(), new_id);
+                }
+
+                for row in &mut rows {
+                    let homework = models::NewHomework {
+                        due_date: row.due_date,
+                        title: row.title.clone(),
+                        description: Some(row.description.clone()),
+                        subject_id: row
+                            .subject
+                            .as_ref()
+                            .and_then(|name| subject_ids.get(&name.to_lowercase()).copied()),
+                        priority: None,
+                        status: None,
+                        estimated_minutes: None,
+                        difficulty: None,
+                        exam_id: None,
+                        tag_ids: None,
+                        due: None,
+                    };
+
+                    let new_homework = super::homeworks::insert_homework(conn, &homework).await?;
+
+                    row.homework_id = Some(new_homework.id);
+                }
+
+                QueryResult::Ok((rows, new_subjects))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(models::CsvImportResult { rows, new_subjects }),
+    ))
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use axum::{extract::State, http::header, response::IntoResponse};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    errors::{self, AppResult},
+    metrics::METRICS,
+    AppState,
+};
+
+const TAG: &str = "Health";
+
+/// Label of the homeworks without a subject
+const NO_SUBJECT: &str = "none";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(metrics))
+}
+
+/// Sets `gauge` to the number of homeworks of each subject in `counts`
+fn set_per_subject(
+    gauge: &prometheus::IntGaugeVec,
+    names: &HashMap<i32, String>,
+    counts: Vec<(Option<i32>, i64)>,
+) {
+    // Subjects without homeworks anymore must not keep their last count
+    gauge.reset();
+
+    for (subject, count) in counts {
+        let subject = subject
+            .and_then(|subject| names.get(&subject))
+            .map_or(NO_SUBJECT, String::as_str);
+
+        gauge.with_label_values(&[subject]).add(count);
+    }
+}
+
+/// Gets the metrics of the app in the Prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = TAG,
+    responses(
+        (status = OK, content_type = "text/plain", body = String)
+    )
+)]
+async fn metrics(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    use crate::schema::homeworks::dsl::*;
+    use crate::schema::subjects;
+
+    let pool = state.pool.state();
+
+    METRICS.db_pool_connections.set(pool.connections.into());
+    METRICS
+        .db_pool_idle_connections
+        .set(pool.idle_connections.into());
+
+    let mut conn = state.pool.get().await?;
+
+    let names = subjects::table
+        .select((subjects::id, subjects::name))
+        .load::<(i32, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let open = homeworks
+        .filter(deleted_at.is_null())
+        .filter(archived_at.is_null())
+        .filter(done.eq(false))
+        .group_by(subject_id);
+
+    let open_counts = open
+        .select((subject_id, diesel::dsl::count_star()))
+        .load::<(Option<i32>, i64)>(&mut conn)
+        .await?;
+
+    let overdue_counts = open
+        .filter(due_date.lt(diesel::dsl::now))
+        .select((subject_id, diesel::dsl::count_star()))
+        .load::<(Option<i32>, i64)>(&mut conn)
+        .await?;
+
+    set_per_subject(&METRICS.open_homeworks, &names, open_counts);
+    set_per_subject(&METRICS.overdue_homeworks, &names, overdue_counts);
+
+    let body = METRICS.encode().map_err(|err| {
+        tracing::error!("cannot encode metrics: {err}");
+        errors::server_error()
+    })?;
+
+    Ok(([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], body))
+}
@@ -1,12 +1,33 @@
+mod exams;
+mod export;
+mod grades;
+mod history;
+mod holidays;
+mod homework_templates;
 mod homeworks;
 mod ical;
+mod import;
+mod metrics;
+mod operations;
+mod status;
 mod subjects;
+mod tags;
+mod terms;
+mod time_entries;
+mod timetable;
+mod trash;
 
-use axum::extract::State;
+use axum::{extract::State, http::StatusCode};
 use diesel_async::RunQueryDsl;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
-use crate::{errors::AppResult, AppState};
+use crate::{
+    config::Feature,
+    errors::{self, AppResult},
+    AppState, Config,
+};
+
+pub(crate) use history::Actor;
 
 #[utoipa::path(
     get,
@@ -14,9 +35,14 @@ use crate::{errors::AppResult, AppState};
     tag = "Health",
     responses(
         (status = OK, description = "App is healthy"),
+        (status = SERVICE_UNAVAILABLE, description = "App is shutting down"),
     )
 )]
 async fn health(State(state): State<AppState>) -> AppResult<()> {
+    if state.supervisor.is_shutting_down() {
+        return Err(errors::custom(StatusCode::SERVICE_UNAVAILABLE));
+    }
+
     let mut conn = state.pool.get().await?;
 
     diesel::sql_query("SELECT 1").execute(&mut conn).await?;
@@ -24,10 +50,51 @@ async fn health(State(state): State<AppState>) -> AppResult<()> {
     Ok(())
 }
 
-pub fn router() -> OpenApiRouter<AppState> {
-    OpenApiRouter::new()
-        .nest("/homeworks", homeworks::router())
-        .nest("/subjects", subjects::router())
-        .nest("/ical", ical::router())
-        .routes(routes!(health))
+/// Probes for orchestrators, served outside of `/api`
+pub fn probes_router() -> OpenApiRouter<AppState> {
+    status::probes_router()
+}
+
+/// Prometheus metrics, served outside of `/api`
+pub fn metrics_router() -> OpenApiRouter<AppState> {
+    metrics::router()
+}
+
+pub fn router(config: &Config) -> OpenApiRouter<AppState> {
+    let mut router = OpenApiRouter::new()
+        .nest(
+            "/homeworks",
+            homeworks::router()
+                .merge(time_entries::homework_router())
+                .merge(history::homework_router()),
+        )
+        .nest(
+            "/subjects",
+            subjects::router()
+                .merge(grades::subject_router())
+                .merge(history::subject_router()),
+        )
+        .nest("/homework-templates", homework_templates::router())
+        .nest("/grades", grades::router())
+        .nest("/exams", exams::router())
+        .nest("/timetable", timetable::router())
+        .nest("/terms", terms::router())
+        .nest("/holidays", holidays::router())
+        .nest("/tags", tags::router())
+        .nest("/time-entries", time_entries::router())
+        .nest("/trash", trash::router())
+        .nest("/operations", operations::router())
+        .nest("/export", export::router())
+        .nest("/status", status::router())
+        .routes(routes!(health));
+
+    if config.is_enabled(Feature::Ical) {
+        router = router.nest("/ical", ical::router());
+    }
+
+    if config.is_enabled(Feature::Import) {
+        router = router.nest("/import", import::router());
+    }
+
+    router
 }
@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use diesel::{pg::Pg, prelude::*};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use super::history::Actor;
+use crate::{
+    errors::{custom, AppResult, BoxedAppError},
+    models,
+    schema::operations,
+    AppState,
+};
+
+const TAG: &str = "Operations";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_operations))
+        .routes(routes!(undo_last_operation))
+        .routes(routes!(undo_operation))
+}
+
+/// Operations that can still be undone, most recent first
+///
+/// Undos cannot be undone themselves, and neither can operations that
+/// purged records.
+fn undoable_operations(state: &AppState) -> operations::BoxedQuery<'static, Pg> {
+    use crate::schema::history_entries;
+
+    let oldest = chrono::Utc::now() - state.undo_window;
+
+    let purges = history_entries::table
+        .filter(history_entries::action.eq(models::HistoryAction::Purge))
+        .filter(history_entries::operation_id.is_not_null())
+        .select(history_entries::operation_id.assume_not_null());
+
+    operations::table
+        .filter(operations::undone_at.is_null())
+        .filter(operations::undo_of.is_null())
+        .filter(operations::performed_at.ge(oldest))
+        .filter(operations::id.ne_all(purges))
+        .order_by(operations::id.desc())
+        .into_boxed()
+}
+
+/// Reverts the changes of an operation, the most recent undoable one if
+/// `target_id` is not set
+async fn undo(
+    conn: &mut AsyncPgConnection,
+    state: &AppState,
+    actor: Actor,
+    target_id: Option<i32>,
+) -> AppResult<models::UndoneOperation> {
+    use crate::schema::{history_entries, homeworks, subjects};
+    use diesel::sql_types::{BigInt, Text};
+
+    conn.transaction::<_, BoxedAppError, _>(|conn| {
+        async move {
+            actor.attribute(conn).await?;
+
+            let operation_id = match target_id {
+                Some(target_id) => {
+                    let operation_id = operations::table
+                        .find(target_id)
+                        .select(operations::id)
+                        .first::<i32>(conn)
+                        .await?;
+
+                    undoable_operations(state)
+                        .filter(operations::id.eq(operation_id))
+                        .select(operations::id)
+                        .first::<i32>(conn)
+                        .await
+                        .optional()?
+                        .ok_or_else(|| custom(StatusCode::CONFLICT))?
+                }
+                None => {
+                    undoable_operations(state)
+                        .select(operations::id)
+                        .first::<i32>(conn)
+                        .await?
+                }
+            };
+
+            // Also guards against undoing the operation concurrently
+            let operation = diesel::update(operations::table.find(operation_id))
+                .filter(operations::undone_at.is_null())
+                .set(operations::undone_at.eq(diesel::dsl::now))
+                .returning(models::Operation::as_returning())
+                .get_result(conn)
+                .await
+                .optional()?
+                .ok_or_else(|| custom(StatusCode::CONFLICT))?;
+
+            let entries = history_entries::table
+                .filter(history_entries::operation_id.eq(operation.id))
+                .order_by(history_entries::id.desc())
+                .load::<models::HistoryEntry>(conn)
+                .await?;
+
+            let records = entries
+                .iter()
+                .map(|entry| (entry.table_name.clone(), entry.record_id))
+                .collect::<HashSet<_>>();
+
+            // Reverting records changed since then would lose those changes
+            if let Some(last_entry_id) = entries.iter().map(|entry| entry.id).max() {
+                let changed_since = history_entries::table
+                    .inner_join(operations::table)
+                    .filter(history_entries::id.gt(last_entry_id))
+                    .filter(history_entries::record_id.eq_any(records.iter().map(|(_, id)| *id)))
+                    .filter(operations::undone_at.is_null())
+                    .filter(operations::undo_of.is_null())
+                    .select((history_entries::table_name, history_entries::record_id))
+                    .load::<(String, i32)>(conn)
+                    .await?;
+
+                if changed_since.iter().any(|record| records.contains(record)) {
+                    return Err(custom(StatusCode::CONFLICT));
+                }
+            }
+
+            diesel::sql_query("SELECT set_config('homeworks.undo_of', $1, true)")
+                .bind::<Text, _>(operation.id.to_string())
+                .execute(conn)
+                .await?;
+
+            for entry in &entries {
+                diesel::sql_query("SELECT revert_history_entry($1)")
+                    .bind::<BigInt, _>(entry.id)
+                    .execute(conn)
+                    .await?;
+            }
+
+            let record_ids = |table: &str| {
+                records
+                    .iter()
+                    .filter(|(table_name, _)| table_name == table)
+                    .map(|(_, id)| *id)
+                    .collect::<Vec<_>>()
+            };
+
+            let homeworks = homeworks::table
+                .filter(homeworks::id.eq_any(record_ids("homeworks")))
+                .select(models::HOMEWORK_ALL_COLUMNS)
+                .order_by(homeworks::id)
+                .load::<models::Homework>(conn)
+                .await?;
+
+            let subjects = subjects::table
+                .filter(subjects::id.eq_any(record_ids("subjects")))
+                .order_by(subjects::id)
+                .load::<models::Subject>(conn)
+                .await?;
+
+            Ok(models::UndoneOperation {
+                operation,
+                homeworks,
+                subjects,
+            })
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Retrieves the operations that can still be undone, most recent first
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::Operation])
+    )
+)]
+async fn list_operations(State(state): State<AppState>) -> AppResult<Json<Vec<models::Operation>>> {
+    let mut conn = state.pool.get().await?;
+
+    let results = undoable_operations(&state)
+        .load::<models::Operation>(&mut conn)
+        .await?;
+
+    Ok(Json(results))
+}
+
+/// Undoes the most recent operation
+#[utoipa::path(
+    post,
+    path = "/undo",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::UndoneOperation),
+        (status = NOT_FOUND, description = "There is no operation to undo"),
+        (status = CONFLICT, description = "The records were changed since the operation")
+    )
+)]
+async fn undo_last_operation(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> AppResult<Json<models::UndoneOperation>> {
+    let mut conn = state.pool.get().await?;
+
+    let undone = undo(&mut conn, &state, actor, None).await?;
+
+    Ok(Json(undone))
+}
+
+/// Undoes a specific operation
+#[utoipa::path(
+    post,
+    path = "/{id}/undo",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::UndoneOperation),
+        (status = NOT_FOUND, description = "The operation does not exist"),
+        (status = CONFLICT, description = "The operation cannot be undone anymore, or the records were changed since")
+    ),
+    params(
+        ("id", description = "Id of the operation"),
+    )
+)]
+async fn undo_operation(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    actor: Actor,
+) -> AppResult<Json<models::UndoneOperation>> {
+    let mut conn = state.pool.get().await?;
+
+    let undone = undo(&mut conn, &state, actor, Some(target_id as i32)).await?;
+
+    Ok(Json(undone))
+}
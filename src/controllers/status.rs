@@ -0,0 +1,161 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use diesel_async::RunQueryDsl;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    db,
+    errors::{self, AppResult},
+    models, AppState,
+};
+
+const TAG: &str = "Health";
+
+pub fn probes_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(livez))
+        .routes(routes!(readyz))
+}
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(status))
+}
+
+/// Tells whether the process is up
+#[utoipa::path(
+    get,
+    path = "/livez",
+    tag = TAG,
+    responses(
+        (status = OK, description = "Process is up"),
+    )
+)]
+async fn livez() {}
+
+/// Tells whether the app can serve traffic
+///
+/// The database must be reachable, the migrations applied and the background
+/// workers running, and the app must not be shutting down.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Readiness),
+        (status = SERVICE_UNAVAILABLE, body = models::Readiness),
+    )
+)]
+async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<models::Readiness>) {
+    let (database, migrations) = match state.pool.get().await {
+        Ok(mut conn) => {
+            let database = diesel::sql_query("SELECT 1")
+                .execute(&mut conn)
+                .await
+                .is_ok();
+
+            let migrations = db::pending_migrations(&mut conn)
+                .await
+                .is_ok_and(|pending| pending.is_empty());
+
+            (database, migrations)
+        }
+        Err(_) => (false, false),
+    };
+
+    let readiness = models::Readiness {
+        database,
+        migrations,
+        workers: state
+            .supervisor
+            .workers()
+            .iter()
+            .all(|worker| worker.running),
+        shutting_down: state.supervisor.is_shutting_down(),
+    };
+
+    let status = if readiness.database
+        && readiness.migrations
+        && readiness.workers
+        && !readiness.shutting_down
+    {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(readiness))
+}
+
+/// Whether `headers` carry `Bearer <token>`, compared in constant time
+fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    let Some(given) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Gets the detailed state of the app
+///
+/// Requires the configured `status_token` as a bearer token, and is not found
+/// when none is configured.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Status),
+        (status = UNAUTHORIZED, description = "Missing or wrong token"),
+        (status = NOT_FOUND, description = "No token is configured"),
+    )
+)]
+async fn status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<models::Status>> {
+    let Some(token) = &state.status_token else {
+        return Err(errors::not_found());
+    };
+
+    if !is_authorized(&headers, token) {
+        return Err(errors::custom(StatusCode::UNAUTHORIZED));
+    }
+
+    let mut conn = state.pool.get().await?;
+
+    let pending_migrations = db::pending_migrations(&mut conn).await?;
+
+    drop(conn);
+
+    let pool = state.pool.state();
+    let now = chrono::Utc::now();
+
+    Ok(Json(models::Status {
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        git_hash: option_env!("GIT_HASH")
+            .filter(|hash| !hash.is_empty())
+            .map(str::to_owned),
+        started_at: state.started_at,
+        uptime_seconds: (now - state.started_at).num_seconds(),
+        pending_migrations,
+        pool: models::PoolStatus {
+            connections: pool.connections,
+            idle_connections: pool.idle_connections,
+            waited: pool.statistics.get_waited,
+            timed_out: pool.statistics.get_timed_out,
+        },
+        workers: state.supervisor.workers(),
+    }))
+}
@@ -4,10 +4,11 @@ use axum::{
 };
 use diesel::prelude::*;
 use diesel::BelongingToDsl;
-use diesel_async::RunQueryDsl;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 use serde::Deserialize;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
+use super::history::Actor;
 use crate::{
     errors::{not_found, AppResult},
     models, AppState,
@@ -19,11 +20,15 @@ pub fn router() -> OpenApiRouter<AppState> {
     OpenApiRouter::new()
         .routes(routes!(list_subjects, create_subject))
         .routes(routes!(find_subject, update_subject, delete_subject))
+        .routes(routes!(restore_subject))
 }
 
 #[derive(Debug, Deserialize, utoipa::IntoParams)]
 struct ListSubjectsParams {
     search: Option<String>,
+
+    /// Also return archived subjects
+    include_archived: Option<bool>,
 }
 
 /// Retrieves all the subjects
@@ -31,6 +36,9 @@ struct ListSubjectsParams {
     get,
     path = "/",
     tag = TAG,
+    params(
+        ListSubjectsParams
+    ),
     responses(
         (status = OK, body = [models::Subject])
     )
@@ -41,7 +49,7 @@ async fn list_subjects(
 ) -> AppResult<Json<Vec<models::Subject>>> {
     use crate::schema::subjects::dsl::*;
 
-    let mut query = subjects.into_boxed();
+    let mut query = subjects.filter(deleted_at.is_null()).into_boxed();
 
     if let Some(search) = params.search {
         let q = format!("%{search}%");
@@ -49,6 +57,10 @@ async fn list_subjects(
         query = query.filter(name.ilike(q));
     }
 
+    if !params.include_archived.unwrap_or_default() {
+        query = query.filter(archived_at.is_null());
+    }
+
     query = query.order(name.asc());
 
     let mut conn = state.pool.get().await?;
@@ -75,21 +87,34 @@ async fn find_subject(
     State(state): State<AppState>,
     Path(target_id): Path<u32>,
 ) -> AppResult<Json<models::SubjectWithHomeworks>> {
+    use crate::schema::homeworks;
     use crate::schema::subjects;
 
     let mut conn = state.pool.get().await?;
 
     let subject = subjects::table
         .find(target_id as i32)
+        .filter(subjects::deleted_at.is_null())
         .first::<models::Subject>(&mut conn)
         .await?;
 
     let homeworks = models::Homework::belonging_to(&subject)
+        .filter(homeworks::deleted_at.is_null())
         .select(models::HOMEWORK_ALL_COLUMNS)
         .load::<models::Homework>(&mut conn)
         .await?;
 
-    Ok(Json(models::SubjectWithHomeworks { subject, homeworks }))
+    let average =
+        super::grades::subject_averages(&mut conn, Some(vec![subject.id]), &Default::default())
+            .await?
+            .pop()
+            .and_then(|average| average.average);
+
+    Ok(Json(models::SubjectWithHomeworks {
+        subject,
+        homeworks,
+        average,
+    }))
 }
 
 /// Creates a new subject
@@ -103,16 +128,26 @@ async fn find_subject(
 )]
 async fn create_subject(
     State(state): State<AppState>,
+    actor: Actor,
     Json(payload): Json<models::NewSubject>,
 ) -> AppResult<Json<models::Subject>> {
     use crate::schema::subjects;
 
     let mut conn = state.pool.get().await?;
 
-    let new_subject = diesel::insert_into(subjects::table)
-        .values(&payload)
-        .returning(models::Subject::as_returning())
-        .get_result(&mut conn)
+    let new_subject = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                diesel::insert_into(subjects::table)
+                    .values(&payload)
+                    .returning(models::Subject::as_returning())
+                    .get_result(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
         .await?;
 
     Ok(Json(new_subject))
@@ -134,24 +169,47 @@ async fn create_subject(
 async fn update_subject(
     State(state): State<AppState>,
     Path(target_id): Path<u32>,
+    actor: Actor,
     Json(payload): Json<models::UpdatedSubject>,
 ) -> AppResult<Json<models::Subject>> {
     use crate::schema::subjects;
     use crate::schema::subjects::dsl::*;
+    use diesel::sql_types::{Nullable, Timestamptz};
 
     let mut conn = state.pool.get().await?;
 
-    let updated_subject = diesel::update(subjects::table)
-        .filter(id.eq(target_id as i32))
-        .set(&payload)
-        .returning(models::Subject::as_returning())
-        .get_result(&mut conn)
+    // Archiving an archived subject again keeps the date it was archived on
+    let archived = payload.archived.map(|archived| {
+        archived_at.eq(diesel::dsl::sql::<Nullable<Timestamptz>>(if archived {
+            "COALESCE(archived_at, NOW())"
+        } else {
+            "NULL"
+        }))
+    });
+
+    let updated_subject = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                diesel::update(subjects::table)
+                    .filter(id.eq(target_id as i32))
+                    .filter(deleted_at.is_null())
+                    .set((&payload.changes, archived, updated_at.eq(diesel::dsl::now)))
+                    .returning(models::Subject::as_returning())
+                    .get_result(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
         .await?;
 
     Ok(Json(updated_subject))
 }
 
-/// Deletes a subject
+/// Moves a subject to the trash
+///
+/// Its homeworks are unlinked from it until it is restored.
 #[utoipa::path(
     delete,
     path = "/{id}",
@@ -167,13 +225,38 @@ async fn update_subject(
 async fn delete_subject(
     State(state): State<AppState>,
     Path(target_id): Path<u32>,
+    actor: Actor,
 ) -> AppResult<()> {
-    use crate::schema::subjects::dsl::*;
+    use crate::schema::homeworks;
+    use crate::schema::subjects;
 
     let mut conn = state.pool.get().await?;
 
-    let deleted_rows = diesel::delete(subjects.filter(id.eq(target_id as i32)))
-        .execute(&mut conn)
+    let deleted_rows = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let deleted_rows = diesel::update(subjects::table)
+                    .filter(subjects::id.eq(target_id as i32))
+                    .filter(subjects::deleted_at.is_null())
+                    .set(subjects::deleted_at.eq(diesel::dsl::now))
+                    .execute(conn)
+                    .await?;
+
+                diesel::update(homeworks::table)
+                    .filter(homeworks::subject_id.eq(target_id as i32))
+                    .set((
+                        homeworks::trashed_subject_id.eq(homeworks::subject_id),
+                        homeworks::subject_id.eq(None::<i32>),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                QueryResult::Ok(deleted_rows)
+            }
+            .scope_boxed()
+        })
         .await?;
 
     if deleted_rows == 0 {
@@ -182,3 +265,57 @@ async fn delete_subject(
 
     Ok(())
 }
+
+/// Restores a subject from the trash, along with the link to its homeworks
+#[utoipa::path(
+    post,
+    path = "/{id}/restore",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Subject),
+        (status = NOT_FOUND, description = "The subject is not in the trash")
+    ),
+    params(
+        ("id", description = "Id of the subject"),
+    )
+)]
+async fn restore_subject(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    actor: Actor,
+) -> AppResult<Json<models::Subject>> {
+    use crate::schema::homeworks;
+    use crate::schema::subjects;
+
+    let mut conn = state.pool.get().await?;
+
+    let restored_subject = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let restored_subject = diesel::update(subjects::table)
+                    .filter(subjects::id.eq(target_id as i32))
+                    .filter(subjects::deleted_at.is_not_null())
+                    .set(subjects::deleted_at.eq(None::<chrono::DateTime<chrono::Utc>>))
+                    .returning(models::Subject::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                diesel::update(homeworks::table)
+                    .filter(homeworks::trashed_subject_id.eq(restored_subject.id))
+                    .set((
+                        homeworks::subject_id.eq(homeworks::trashed_subject_id),
+                        homeworks::trashed_subject_id.eq(None::<i32>),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                QueryResult::Ok(restored_subject)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(restored_subject))
+}
@@ -0,0 +1,177 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::Deserialize;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    errors::{not_found, AppResult},
+    models, AppState,
+};
+
+const TAG: &str = "Tags";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_tags, create_tag))
+        .routes(routes!(get_tag, update_tag, delete_tag))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct ListTagsParams {
+    search: Option<String>,
+}
+
+/// Retrieves all the tags
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::Tag])
+    )
+)]
+async fn list_tags(
+    State(state): State<AppState>,
+    Query(params): Query<ListTagsParams>,
+) -> AppResult<Json<Vec<models::Tag>>> {
+    use crate::schema::tags::dsl::*;
+
+    let mut query = tags.into_boxed();
+
+    if let Some(search) = params.search {
+        let q = format!("%{search}%");
+
+        query = query.filter(name.ilike(q));
+    }
+
+    query = query.order(name.asc());
+
+    let mut conn = state.pool.get().await?;
+
+    let results = query.load::<models::Tag>(&mut conn).await?;
+
+    Ok(Json(results))
+}
+
+/// Retrieves a specific tag
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Tag),
+        (status = NOT_FOUND, description = "The tag does not exist")
+    ),
+    params(
+        ("id", description = "Id of the tag"),
+    )
+)]
+async fn get_tag(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::Tag>> {
+    use crate::schema::tags;
+
+    let mut conn = state.pool.get().await?;
+
+    let tag = tags::table
+        .find(target_id as i32)
+        .first::<models::Tag>(&mut conn)
+        .await?;
+
+    Ok(Json(tag))
+}
+
+/// Creates a new tag
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Tag),
+        (status = CONFLICT, description = "A tag with the same name already exists")
+    )
+)]
+async fn create_tag(
+    State(state): State<AppState>,
+    Json(payload): Json<models::NewTag>,
+) -> AppResult<Json<models::Tag>> {
+    use crate::schema::tags;
+
+    let mut conn = state.pool.get().await?;
+
+    let new_tag = diesel::insert_into(tags::table)
+        .values(&payload)
+        .returning(models::Tag::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(new_tag))
+}
+
+/// Updates a tag
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Tag),
+        (status = NOT_FOUND, description = "The tag does not exist"),
+        (status = CONFLICT, description = "A tag with the same name already exists")
+    ),
+    params(
+        ("id", description = "Id of the tag"),
+    )
+)]
+async fn update_tag(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::UpdatedTag>,
+) -> AppResult<Json<models::Tag>> {
+    use crate::schema::tags;
+    use crate::schema::tags::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let updated_tag = diesel::update(tags::table)
+        .filter(id.eq(target_id as i32))
+        .set(&payload)
+        .returning(models::Tag::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(updated_tag))
+}
+
+/// Deletes a tag
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, description = "The tag does not exist")
+    ),
+    params(
+        ("id", description = "Id of the tag"),
+    )
+)]
+async fn delete_tag(State(state): State<AppState>, Path(target_id): Path<u32>) -> AppResult<()> {
+    use crate::schema::tags::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let deleted_rows = diesel::delete(tags.filter(id.eq(target_id as i32)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted_rows == 0 {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
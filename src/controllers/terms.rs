@@ -0,0 +1,245 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use super::history::Actor;
+use crate::{
+    errors::{not_found, AppResult},
+    models, timetable, AppState,
+};
+
+const TAG: &str = "Terms";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_terms, create_term))
+        .routes(routes!(rollover))
+        .routes(routes!(get_term, update_term, delete_term))
+}
+
+/// Resolves the instants between which a term takes place
+pub(super) async fn term_range(
+    conn: &mut AsyncPgConnection,
+    state: &AppState,
+    term_id: u32,
+) -> QueryResult<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+    use crate::schema::terms;
+
+    let term = terms::table
+        .find(term_id as i32)
+        .first::<models::Term>(conn)
+        .await?;
+
+    Ok(timetable::date_range(
+        term.starts_on,
+        term.ends_on,
+        state.timezone,
+    ))
+}
+
+/// Retrieves all the terms
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::Term])
+    )
+)]
+async fn list_terms(State(state): State<AppState>) -> AppResult<Json<Vec<models::Term>>> {
+    use crate::schema::terms::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let results = terms
+        .order_by((starts_on, id))
+        .load::<models::Term>(&mut conn)
+        .await?;
+
+    Ok(Json(results))
+}
+
+/// Retrieves a specific term
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Term),
+        (status = NOT_FOUND, description = "The term does not exist")
+    ),
+    params(
+        ("id", description = "Id of the term"),
+    )
+)]
+async fn get_term(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::Term>> {
+    use crate::schema::terms;
+
+    let mut conn = state.pool.get().await?;
+
+    let term = terms::table
+        .find(target_id as i32)
+        .first::<models::Term>(&mut conn)
+        .await?;
+
+    Ok(Json(term))
+}
+
+/// Creates a new term
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Term),
+        (status = UNPROCESSABLE_ENTITY, description = "The term ends before it starts")
+    )
+)]
+async fn create_term(
+    State(state): State<AppState>,
+    Json(payload): Json<models::NewTerm>,
+) -> AppResult<Json<models::Term>> {
+    use crate::schema::terms;
+
+    let mut conn = state.pool.get().await?;
+
+    let new_term = diesel::insert_into(terms::table)
+        .values(&payload)
+        .returning(models::Term::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(new_term))
+}
+
+/// Updates a term
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Term),
+        (status = NOT_FOUND, description = "The term does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The term ends before it starts")
+    ),
+    params(
+        ("id", description = "Id of the term"),
+    )
+)]
+async fn update_term(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::UpdatedTerm>,
+) -> AppResult<Json<models::Term>> {
+    use crate::schema::terms::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let updated_term = diesel::update(terms)
+        .filter(id.eq(target_id as i32))
+        .set(&payload)
+        .returning(models::Term::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(updated_term))
+}
+
+/// Deletes a term
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, description = "The term does not exist")
+    ),
+    params(
+        ("id", description = "Id of the term"),
+    )
+)]
+async fn delete_term(State(state): State<AppState>, Path(target_id): Path<u32>) -> AppResult<()> {
+    use crate::schema::terms::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let deleted_rows = diesel::delete(terms.filter(id.eq(target_id as i32)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted_rows == 0 {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
+
+/// Starts a new academic year
+///
+/// Archives the subjects created and the homeworks due before the start of
+/// the new year. Homeworks without due date are archived if they were created
+/// before it. Archived homeworks are hidden from listings by default.
+#[utoipa::path(
+    post,
+    path = "/rollover",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::RolloverSummary)
+    )
+)]
+async fn rollover(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(payload): Json<models::Rollover>,
+) -> AppResult<Json<models::RolloverSummary>> {
+    use crate::schema::homeworks;
+    use crate::schema::subjects;
+
+    let (year_start, _) =
+        timetable::date_range(payload.starts_on, payload.starts_on, state.timezone);
+
+    let mut conn = state.pool.get().await?;
+
+    let summary = conn
+        .transaction(|conn| {
+            async move {
+                actor.attribute(conn).await?;
+
+                let homeworks = diesel::update(homeworks::table)
+                    .filter(homeworks::archived_at.is_null())
+                    .filter(
+                        homeworks::due_date.lt(year_start).or(homeworks::due_date
+                            .is_null()
+                            .and(homeworks::created_at.lt(year_start))),
+                    )
+                    .set(homeworks::archived_at.eq(diesel::dsl::now))
+                    .execute(conn)
+                    .await?;
+
+                let subjects = diesel::update(subjects::table)
+                    .filter(subjects::archived_at.is_null())
+                    .filter(subjects::created_at.lt(year_start))
+                    .set(subjects::archived_at.eq(diesel::dsl::now))
+                    .execute(conn)
+                    .await?;
+
+                QueryResult::Ok(models::RolloverSummary {
+                    subjects,
+                    homeworks,
+                })
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(summary))
+}
@@ -0,0 +1,415 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use diesel::{expression::SqlLiteral, prelude::*, sql_types::BigInt};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::Deserialize;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    errors::{not_found, unprocessable_entity, AppResult},
+    models, AppState,
+};
+
+const TAG: &str = "Time tracking";
+
+/// Routes nested under a homework
+pub fn homework_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_time_entries, create_time_entry))
+        .routes(routes!(start_timer))
+        .routes(routes!(stop_timer))
+}
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(running_timer))
+        .routes(routes!(update_time_entry, delete_time_entry))
+        .routes(routes!(subject_stats))
+        .routes(routes!(week_stats))
+}
+
+/// Stops the running timer of any of the homeworks, as they go to the trash
+pub(super) async fn stop_timers(
+    conn: &mut AsyncPgConnection,
+    homework_ids: &[i32],
+) -> QueryResult<usize> {
+    use crate::schema::time_entries::dsl::*;
+
+    diesel::update(time_entries)
+        .filter(homework_id.eq_any(homework_ids))
+        .filter(ended_at.is_null())
+        .set(ended_at.eq(diesel::dsl::now))
+        .execute(conn)
+        .await
+}
+
+/// Sum of the durations of the selected time entries, in seconds
+///
+/// A running timer counts up to now.
+pub(super) fn tracked_seconds() -> SqlLiteral<BigInt> {
+    diesel::dsl::sql::<BigInt>(
+        "CAST(COALESCE(SUM(EXTRACT(EPOCH FROM COALESCE(ended_at, NOW()) - started_at)), 0) AS BIGINT)",
+    )
+}
+
+/// Retrieves the time entries of a homework
+#[utoipa::path(
+    get,
+    path = "/{id}/time-entries",
+    tag = TAG,
+    responses(
+        (status = OK, body = [models::TimeEntry]),
+        (status = NOT_FOUND, description = "The homework does not exist or is in the trash")
+    ),
+    params(
+        ("id", description = "Id of the homework"),
+    )
+)]
+async fn list_time_entries(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<Vec<models::TimeEntry>>> {
+    use crate::schema::homeworks;
+    use crate::schema::time_entries;
+
+    let mut conn = state.pool.get().await?;
+
+    let homework = homeworks::table
+        .find(target_id as i32)
+        .filter(homeworks::deleted_at.is_null())
+        .select(models::HOMEWORK_ALL_COLUMNS)
+        .first::<models::Homework>(&mut conn)
+        .await?;
+
+    let results = models::TimeEntry::belonging_to(&homework)
+        .order_by(time_entries::started_at)
+        .load::<models::TimeEntry>(&mut conn)
+        .await?;
+
+    Ok(Json(results))
+}
+
+/// Manually records time spent on a homework
+#[utoipa::path(
+    post,
+    path = "/{id}/time-entries",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::TimeEntry),
+        (status = UNPROCESSABLE_ENTITY, description = "The homework does not exist or is in the trash, or the entry ends before it starts")
+    ),
+    params(
+        ("id", description = "Id of the homework"),
+    )
+)]
+async fn create_time_entry(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::NewTimeEntry>,
+) -> AppResult<Json<models::TimeEntry>> {
+    use crate::schema::homeworks;
+    use crate::schema::time_entries;
+
+    let mut conn = state.pool.get().await?;
+
+    let homework_id = homeworks::table
+        .find(target_id as i32)
+        .filter(homeworks::deleted_at.is_null())
+        .select(homeworks::id)
+        .first::<i32>(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(unprocessable_entity)?;
+
+    let new_entry = diesel::insert_into(time_entries::table)
+        .values((&payload, time_entries::homework_id.eq(homework_id)))
+        .returning(models::TimeEntry::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(new_entry))
+}
+
+/// Starts a timer on a homework
+#[utoipa::path(
+    post,
+    path = "/{id}/timer/start",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::TimeEntry),
+        (status = NOT_FOUND, description = "The homework does not exist or is in the trash"),
+        (status = CONFLICT, description = "A timer is already running")
+    ),
+    params(
+        ("id", description = "Id of the homework"),
+    )
+)]
+async fn start_timer(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::TimeEntry>> {
+    use crate::schema::homeworks;
+    use crate::schema::time_entries;
+
+    let mut conn = state.pool.get().await?;
+
+    let homework_id = homeworks::table
+        .find(target_id as i32)
+        .filter(homeworks::deleted_at.is_null())
+        .select(homeworks::id)
+        .first::<i32>(&mut conn)
+        .await?;
+
+    let new_entry = diesel::insert_into(time_entries::table)
+        .values(time_entries::homework_id.eq(homework_id))
+        .returning(models::TimeEntry::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(new_entry))
+}
+
+/// Stops the running timer of a homework
+#[utoipa::path(
+    post,
+    path = "/{id}/timer/stop",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::TimeEntry),
+        (status = NOT_FOUND, description = "No timer is running for this homework")
+    ),
+    params(
+        ("id", description = "Id of the homework"),
+    )
+)]
+async fn stop_timer(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::TimeEntry>> {
+    use crate::schema::time_entries::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let stopped_entry = diesel::update(time_entries)
+        .filter(homework_id.eq(target_id as i32))
+        .filter(ended_at.is_null())
+        .set(ended_at.eq(diesel::dsl::now))
+        .returning(models::TimeEntry::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(stopped_entry))
+}
+
+/// Retrieves the running timer, if any
+#[utoipa::path(
+    get,
+    path = "/running",
+    tag = TAG,
+    responses(
+        (status = OK, body = Option<models::TimeEntry>)
+    )
+)]
+async fn running_timer(
+    State(state): State<AppState>,
+) -> AppResult<Json<Option<models::TimeEntry>>> {
+    use crate::schema::time_entries::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let running = time_entries
+        .filter(ended_at.is_null())
+        .first::<models::TimeEntry>(&mut conn)
+        .await
+        .optional()?;
+
+    Ok(Json(running))
+}
+
+/// Updates a time entry
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::TimeEntry),
+        (status = NOT_FOUND, description = "The time entry does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The entry ends before it starts")
+    ),
+    params(
+        ("id", description = "Id of the time entry"),
+    )
+)]
+async fn update_time_entry(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::UpdatedTimeEntry>,
+) -> AppResult<Json<models::TimeEntry>> {
+    use crate::schema::time_entries::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let updated_entry = diesel::update(time_entries)
+        .filter(id.eq(target_id as i32))
+        .set(&payload)
+        .returning(models::TimeEntry::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(updated_entry))
+}
+
+/// Deletes a time entry
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, description = "The time entry does not exist")
+    ),
+    params(
+        ("id", description = "Id of the time entry"),
+    )
+)]
+async fn delete_time_entry(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<()> {
+    use crate::schema::time_entries::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let deleted_rows = diesel::delete(time_entries.filter(id.eq(target_id as i32)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted_rows == 0 {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct StatsParams {
+    /// Only count time entries started after `start`
+    start: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only count time entries started before `end`
+    end: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only count time entries started during a term
+    term_id: Option<u32>,
+}
+
+impl StatsParams {
+    /// Narrows `start` and `end` to the term, if any
+    async fn resolve_term(
+        mut self,
+        conn: &mut AsyncPgConnection,
+        state: &AppState,
+    ) -> QueryResult<Self> {
+        if let Some(term_id) = self.term_id {
+            let (term_start, term_end) = super::terms::term_range(conn, state, term_id).await?;
+
+            self.start = Some(self.start.map_or(term_start, |start| start.max(term_start)));
+            self.end = Some(self.end.map_or(term_end, |end| end.min(term_end)));
+        }
+
+        Ok(self)
+    }
+}
+
+/// Aggregates the tracked time per subject
+#[utoipa::path(
+    get,
+    path = "/stats/subjects",
+    tag = TAG,
+    params(
+        StatsParams
+    ),
+    responses(
+        (status = OK, body = [models::SubjectTimeStats])
+    )
+)]
+async fn subject_stats(
+    State(state): State<AppState>,
+    Query(params): Query<StatsParams>,
+) -> AppResult<Json<Vec<models::SubjectTimeStats>>> {
+    use diesel::sql_types::{Nullable, Timestamptz};
+
+    let mut conn = state.pool.get().await?;
+
+    let params = params.resolve_term(&mut conn, &state).await?;
+
+    let results = diesel::sql_query(
+        "WITH per_homework AS (
+            SELECT homework_id,
+                   SUM(EXTRACT(EPOCH FROM COALESCE(ended_at, NOW()) - started_at)) AS seconds
+            FROM time_entries
+            WHERE ($1 IS NULL OR started_at >= $1) AND ($2 IS NULL OR started_at < $2)
+            GROUP BY homework_id
+        )
+        SELECT homeworks.subject_id,
+               COUNT(*) AS homeworks,
+               CAST(SUM(per_homework.seconds) AS BIGINT) AS tracked_seconds,
+               CAST(SUM(homeworks.estimated_minutes) * 60 AS BIGINT) AS estimated_seconds
+        FROM per_homework
+        INNER JOIN homeworks ON homeworks.id = per_homework.homework_id
+        WHERE homeworks.deleted_at IS NULL
+        GROUP BY homeworks.subject_id
+        ORDER BY homeworks.subject_id",
+    )
+    .bind::<Nullable<Timestamptz>, _>(params.start)
+    .bind::<Nullable<Timestamptz>, _>(params.end)
+    .load::<models::SubjectTimeStats>(&mut conn)
+    .await?;
+
+    Ok(Json(results))
+}
+
+/// Aggregates the tracked time per week, weeks starting on monday in the
+/// configured timezone
+#[utoipa::path(
+    get,
+    path = "/stats/weeks",
+    tag = TAG,
+    params(
+        StatsParams
+    ),
+    responses(
+        (status = OK, body = [models::WeekTimeStats])
+    )
+)]
+async fn week_stats(
+    State(state): State<AppState>,
+    Query(params): Query<StatsParams>,
+) -> AppResult<Json<Vec<models::WeekTimeStats>>> {
+    use diesel::sql_types::{Nullable, Text, Timestamptz};
+
+    let mut conn = state.pool.get().await?;
+
+    let params = params.resolve_term(&mut conn, &state).await?;
+
+    let results = diesel::sql_query(
+        "SELECT CAST(date_trunc('week', started_at AT TIME ZONE $3) AS DATE) AS week,
+                CAST(SUM(EXTRACT(EPOCH FROM COALESCE(ended_at, NOW()) - started_at)) AS BIGINT)
+                    AS tracked_seconds
+        FROM time_entries
+        INNER JOIN homeworks ON homeworks.id = time_entries.homework_id
+        WHERE ($1 IS NULL OR started_at >= $1) AND ($2 IS NULL OR started_at < $2)
+            AND homeworks.deleted_at IS NULL
+        GROUP BY 1
+        ORDER BY 1",
+    )
+    .bind::<Nullable<Timestamptz>, _>(params.start)
+    .bind::<Nullable<Timestamptz>, _>(params.end)
+    .bind::<Text, _>(state.timezone.name())
+    .load::<models::WeekTimeStats>(&mut conn)
+    .await?;
+
+    Ok(Json(results))
+}
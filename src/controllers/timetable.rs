@@ -0,0 +1,259 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::Deserialize;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    errors::{not_found, AppResult},
+    models, timetable, utils, AppState,
+};
+
+const TAG: &str = "Timetable";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list_slots, create_slot))
+        .routes(routes!(next_lesson))
+        .routes(routes!(get_slot, update_slot, delete_slot))
+}
+
+/// Resolves the start of the next lesson of a subject, outside of holidays
+pub(super) async fn resolve_next_lesson(
+    conn: &mut AsyncPgConnection,
+    state: &AppState,
+    target_subject_id: i32,
+    after: chrono::DateTime<chrono::Utc>,
+) -> QueryResult<Option<chrono::DateTime<chrono::Utc>>> {
+    use crate::schema::holidays;
+    use crate::schema::timetable_slots::dsl::*;
+
+    let slots = timetable_slots
+        .filter(subject_id.eq(target_subject_id))
+        .load::<models::TimetableSlot>(conn)
+        .await?;
+
+    let holidays = holidays::table
+        .filter(holidays::ends_on.ge(after.with_timezone(&state.timezone).date_naive()))
+        .load::<models::Holiday>(conn)
+        .await?;
+
+    Ok(timetable::next_lesson(
+        &slots,
+        &holidays,
+        after,
+        state.timezone,
+        state.timetable_a_week,
+    ))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct ListSlotsParams {
+    /// Filter by subjects
+    subject_ids: Option<utils::IdSequence>,
+}
+
+/// Retrieves the weekly timetable
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    params(
+        ListSlotsParams
+    ),
+    responses(
+        (status = OK, body = [models::TimetableSlotWithSubject])
+    )
+)]
+async fn list_slots(
+    State(state): State<AppState>,
+    Query(params): Query<ListSlotsParams>,
+) -> AppResult<Json<Vec<models::TimetableSlotWithSubject>>> {
+    use crate::schema::subjects;
+    use crate::schema::timetable_slots::dsl::*;
+
+    let mut query = timetable_slots
+        .inner_join(subjects::table)
+        .filter(subjects::deleted_at.is_null())
+        .select((
+            models::TimetableSlot::as_select(),
+            models::Subject::as_select(),
+        ))
+        .order_by((weekday, starts_at))
+        .into_boxed();
+
+    if let Some(subject_ids) = params.subject_ids {
+        if !subject_ids.is_empty() {
+            query = query.filter(subject_id.eq_any(subject_ids.ids()));
+        }
+    }
+
+    let mut conn = state.pool.get().await?;
+
+    let results = query
+        .load::<(models::TimetableSlot, models::Subject)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(slot, subject)| models::TimetableSlotWithSubject { slot, subject })
+        .collect();
+
+    Ok(Json(results))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+struct NextLessonParams {
+    /// Subject of the lesson
+    subject_id: u32,
+
+    /// Look for a lesson after this date, now by default
+    after: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Retrieves the start of the next lesson of a subject
+#[utoipa::path(
+    get,
+    path = "/next-lesson",
+    tag = TAG,
+    params(
+        NextLessonParams
+    ),
+    responses(
+        (status = OK, body = chrono::DateTime<chrono::Utc>),
+        (status = NOT_FOUND, description = "The subject has no lesson in the timetable")
+    )
+)]
+async fn next_lesson(
+    State(state): State<AppState>,
+    Query(params): Query<NextLessonParams>,
+) -> AppResult<Json<chrono::DateTime<chrono::Utc>>> {
+    let mut conn = state.pool.get().await?;
+
+    let after = params.after.unwrap_or_else(chrono::Utc::now);
+
+    let lesson = resolve_next_lesson(&mut conn, &state, params.subject_id as i32, after)
+        .await?
+        .ok_or_else(not_found)?;
+
+    Ok(Json(lesson))
+}
+
+/// Retrieves a specific timetable slot
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::TimetableSlot),
+        (status = NOT_FOUND, description = "The slot does not exist")
+    ),
+    params(
+        ("id", description = "Id of the slot"),
+    )
+)]
+async fn get_slot(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+) -> AppResult<Json<models::TimetableSlot>> {
+    use crate::schema::timetable_slots;
+
+    let mut conn = state.pool.get().await?;
+
+    let slot = timetable_slots::table
+        .find(target_id as i32)
+        .first::<models::TimetableSlot>(&mut conn)
+        .await?;
+
+    Ok(Json(slot))
+}
+
+/// Adds a slot to the timetable
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::TimetableSlot),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject does not exist or the slot is invalid")
+    )
+)]
+async fn create_slot(
+    State(state): State<AppState>,
+    Json(payload): Json<models::NewTimetableSlot>,
+) -> AppResult<Json<models::TimetableSlot>> {
+    use crate::schema::timetable_slots;
+
+    let mut conn = state.pool.get().await?;
+
+    let new_slot = diesel::insert_into(timetable_slots::table)
+        .values(&payload)
+        .returning(models::TimetableSlot::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(new_slot))
+}
+
+/// Updates a timetable slot
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::TimetableSlot),
+        (status = NOT_FOUND, description = "The slot does not exist"),
+        (status = UNPROCESSABLE_ENTITY, description = "The subject does not exist or the slot is invalid")
+    ),
+    params(
+        ("id", description = "Id of the slot"),
+    )
+)]
+async fn update_slot(
+    State(state): State<AppState>,
+    Path(target_id): Path<u32>,
+    Json(payload): Json<models::UpdatedTimetableSlot>,
+) -> AppResult<Json<models::TimetableSlot>> {
+    use crate::schema::timetable_slots::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let updated_slot = diesel::update(timetable_slots)
+        .filter(id.eq(target_id as i32))
+        .set(&payload)
+        .returning(models::TimetableSlot::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    Ok(Json(updated_slot))
+}
+
+/// Removes a slot from the timetable
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = TAG,
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, description = "The slot does not exist")
+    ),
+    params(
+        ("id", description = "Id of the slot"),
+    )
+)]
+async fn delete_slot(State(state): State<AppState>, Path(target_id): Path<u32>) -> AppResult<()> {
+    use crate::schema::timetable_slots::dsl::*;
+
+    let mut conn = state.pool.get().await?;
+
+    let deleted_rows = diesel::delete(timetable_slots.filter(id.eq(target_id as i32)))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted_rows == 0 {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
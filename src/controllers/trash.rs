@@ -0,0 +1,49 @@
+use axum::{extract::State, Json};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{errors::AppResult, models, AppState};
+
+const TAG: &str = "Trash";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(list_trash))
+}
+
+/// Retrieves the homeworks and subjects in the trash, most recently deleted
+/// first
+///
+/// They are permanently deleted once the retention period is over.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = TAG,
+    responses(
+        (status = OK, body = models::Trash)
+    )
+)]
+async fn list_trash(State(state): State<AppState>) -> AppResult<Json<models::Trash>> {
+    use crate::schema::homeworks;
+    use crate::schema::subjects;
+
+    let mut conn = state.pool.get().await?;
+
+    let homeworks = homeworks::table
+        .filter(homeworks::deleted_at.is_not_null())
+        .select(models::HOMEWORK_ALL_COLUMNS)
+        .order_by((homeworks::deleted_at.desc(), homeworks::id))
+        .load::<models::Homework>(&mut conn)
+        .await?;
+
+    let subjects = subjects::table
+        .filter(subjects::deleted_at.is_not_null())
+        .order_by((subjects::deleted_at.desc(), subjects::id))
+        .load::<models::Subject>(&mut conn)
+        .await?;
+
+    Ok(Json(models::Trash {
+        homeworks,
+        subjects,
+    }))
+}
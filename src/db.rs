@@ -1,29 +1,73 @@
+use std::path::Path;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
-use color_eyre::eyre::Context;
-use diesel::ConnectionResult;
+use color_eyre::eyre::{eyre, Context};
+use diesel::migration::MigrationSource;
+use diesel::pg::Pg;
+use diesel::sql_types::Text;
+use diesel::{ConnectionError, ConnectionResult, QueryableByName};
 use diesel_async::async_connection_wrapper::AsyncConnectionWrapper;
 use diesel_async::pooled_connection::bb8;
 use diesel_async::pooled_connection::AsyncDieselConnectionManager;
 use diesel_async::pooled_connection::ManagerConfig;
-use diesel_async::AsyncPgConnection;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use futures_util::future::BoxFuture;
 use futures_util::FutureExt;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
 use tokio::task::spawn_blocking;
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+use crate::{
+    config::SslMode,
+    errors::{self, AppResult},
+    telemetry::QueryInstrumentation,
+    Config,
+};
 
 pub type Pool = bb8::Pool<AsyncPgConnection>;
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 
-pub async fn create_database(database_url: &str) -> color_eyre::Result<Pool> {
-    let mut config = ManagerConfig::default();
-    config.custom_setup = Box::new(establish_tls_connection);
+/// Maximum number of connections in the pool, unless configured
+pub const DEFAULT_POOL_MAX_SIZE: u32 = 10;
+
+/// Number of seconds to wait for a connection from the pool, unless configured
+pub const DEFAULT_POOL_CONNECTION_TIMEOUT_SECONDS: u64 = 30;
+
+/// Number of seconds after which an idle connection is closed, unless
+/// configured
+pub const DEFAULT_POOL_IDLE_TIMEOUT_SECONDS: u64 = 10 * 60;
+
+/// Number of seconds after which a connection is replaced, unless configured
+pub const DEFAULT_POOL_MAX_LIFETIME_SECONDS: u64 = 30 * 60;
 
-    let mgr =
-        AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(database_url, config);
+pub async fn create_database(config: &Config) -> color_eyre::Result<Pool> {
+    let connector = Connector::new(config)?;
+
+    let mut manager_config = ManagerConfig::default();
+    manager_config.custom_setup = Box::new(move |database_url| connector.connect(database_url));
+
+    let mgr = AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(
+        &config.database_url,
+        manager_config,
+    );
+
+    // A zero duration turns the limit off
+    let optional = |seconds| (seconds > 0).then(|| Duration::from_secs(seconds));
 
     let pool = bb8::Pool::builder()
+        .max_size(config.pool_max_size)
+        .min_idle(config.pool_min_idle)
+        .connection_timeout(Duration::from_secs(config.pool_connection_timeout_seconds))
+        .idle_timeout(optional(config.pool_idle_timeout_seconds))
+        .max_lifetime(optional(config.pool_max_lifetime_seconds))
         .test_on_check_out(true)
         .build(mgr)
         .await
@@ -32,43 +76,251 @@ pub async fn create_database(database_url: &str) -> color_eyre::Result<Pool> {
     Ok(pool)
 }
 
-pub fn establish_tls_connection(
-    database_url: &str,
-) -> BoxFuture<ConnectionResult<AsyncPgConnection>> {
-    use rustls_platform_verifier::ConfigVerifierExt;
-
-    let fut = async {
-        // We first set up the way we want rustls to work.
-        let rustls_config = rustls::ClientConfig::with_platform_verifier();
-        let tls_config = tokio_postgres_rustls::MakeRustlsConnect::new(rustls_config);
-        let config = tokio_postgres::Config::from_str(database_url).expect("invalid postgres url");
-
-        let (client, conn) = config
-            .connect(tls_config)
-            .await
-            .map_err(|e| diesel::ConnectionError::BadConnection(e.to_string()))?;
-
-        AsyncPgConnection::try_from_client_and_connection(client, conn).await
-    };
-    fut.boxed()
+/// Opens a single connection to the database, outside of the pool
+pub async fn establish_connection(config: &Config) -> color_eyre::Result<AsyncPgConnection> {
+    Connector::new(config)?
+        .connect(&config.database_url)
+        .await
+        .wrap_err("cannot establish connection to postgres")
 }
 
-pub async fn run_migrations(database_url: &str) -> color_eyre::Result<()> {
-    tracing::info!("running pending migrations");
+/// Opens connections to postgres, secured according to the configured
+/// `sslmode`
+#[derive(Clone)]
+pub struct Connector {
+    ssl_mode: SslMode,
+    tls: MakeRustlsConnect,
+}
 
-    let conn = establish_tls_connection(database_url)
-        .await
-        .wrap_err("cannot establish connection to postgres")?;
+impl Connector {
+    /// Reads the certificates the connections need, failing if any is invalid
+    pub fn new(config: &Config) -> color_eyre::Result<Self> {
+        use rustls_platform_verifier::BuilderVerifierExt;
+
+        let builder = rustls::ClientConfig::builder();
+
+        let builder = match (
+            config.database_ssl_accept_invalid_certs,
+            &config.database_ssl_root_cert,
+        ) {
+            (true, _) => {
+                let provider = builder.crypto_provider().clone();
+
+                builder
+                    .dangerous()
+                    .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+            }
+            (false, Some(root_cert)) => {
+                let mut roots = rustls::RootCertStore::empty();
+
+                for cert in read_certs(root_cert)? {
+                    roots.add(cert).wrap_err_with(|| {
+                        format!("invalid certificate in {}", root_cert.display())
+                    })?;
+                }
+
+                builder.with_root_certificates(roots)
+            }
+            (false, None) => builder.with_platform_verifier(),
+        };
+
+        let tls_config = match (&config.database_ssl_cert, &config.database_ssl_key) {
+            (Some(cert), Some(key)) => {
+                let key = PrivateKeyDer::from_pem_file(key)
+                    .map_err(|err| eyre!("cannot read key from {}: {err}", key.display()))?;
+
+                builder
+                    .with_client_auth_cert(read_certs(cert)?, key)
+                    .wrap_err("invalid client certificate")?
+            }
+            _ => builder.with_no_client_auth(),
+        };
+
+        Ok(Self {
+            ssl_mode: config.database_ssl_mode,
+            tls: MakeRustlsConnect::new(tls_config),
+        })
+    }
+
+    pub fn connect<'a>(
+        &self,
+        database_url: &'a str,
+    ) -> BoxFuture<'a, ConnectionResult<AsyncPgConnection>> {
+        let connector = self.clone();
+
+        let fut = async move {
+            let mut config = tokio_postgres::Config::from_str(database_url)
+                .map_err(|err| ConnectionError::InvalidConnectionUrl(err.to_string()))?;
+
+            config.ssl_mode(match connector.ssl_mode {
+                SslMode::Disable => tokio_postgres::config::SslMode::Disable,
+                SslMode::Prefer => tokio_postgres::config::SslMode::Prefer,
+                SslMode::Require | SslMode::VerifyFull => tokio_postgres::config::SslMode::Require,
+            });
+
+            let (client, conn) = config
+                .connect(connector.tls)
+                .await
+                .map_err(|e| ConnectionError::BadConnection(e.to_string()))?;
+
+            let mut conn = AsyncPgConnection::try_from_client_and_connection(client, conn).await?;
+            conn.set_instrumentation(QueryInstrumentation::default());
+
+            Ok(conn)
+        };
+        fut.boxed()
+    }
+}
+
+fn read_certs(path: &Path) -> color_eyre::Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| eyre!("cannot read certificates from {}: {err}", path.display()))?;
+
+    if certs.is_empty() {
+        return Err(eyre!("no certificate in {}", path.display()));
+    }
+
+    Ok(certs)
+}
+
+/// Encrypts the connection without authenticating the server, like libpq does
+/// in the `prefer` and `require` modes, when explicitly asked to
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
 
-    let mut conn = AsyncConnectionWrapper::<AsyncPgConnection>::from(conn);
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Connection to run migrations with, which diesel only does synchronously
+async fn migration_connection(
+    config: &Config,
+) -> color_eyre::Result<AsyncConnectionWrapper<AsyncPgConnection>> {
+    let conn = establish_connection(config).await?;
+
+    Ok(AsyncConnectionWrapper::<AsyncPgConnection>::from(conn))
+}
+
+pub async fn run_migrations(config: &Config) -> color_eyre::Result<()> {
+    tracing::info!("running pending migrations");
+
+    let mut conn = migration_connection(config).await?;
 
     spawn_blocking(move || {
         conn.run_pending_migrations(MIGRATIONS)
-            .expect("cannot run pending migrations");
+            .map(|_| ())
+            .map_err(|err| eyre!("cannot run pending migrations: {err}"))
     })
-    .await?;
+    .await??;
 
     tracing::info!("pending migrations done");
 
     Ok(())
 }
+
+/// Reverts the last applied migration, returning its version
+pub async fn revert_last_migration(config: &Config) -> color_eyre::Result<String> {
+    let mut conn = migration_connection(config).await?;
+
+    spawn_blocking(move || {
+        conn.revert_last_migration(MIGRATIONS)
+            .map(|version| version.to_string())
+            .map_err(|err| eyre!("cannot revert migration: {err}"))
+    })
+    .await?
+}
+
+/// Names of the migrations not applied yet, oldest first, checked through an
+/// async connection
+pub async fn pending_migrations(conn: &mut AsyncPgConnection) -> AppResult<Vec<String>> {
+    #[derive(QueryableByName)]
+    struct AppliedMigration {
+        #[diesel(sql_type = Text)]
+        version: String,
+    }
+
+    let applied = diesel::sql_query("SELECT version FROM __diesel_schema_migrations")
+        .load::<AppliedMigration>(conn)
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect::<Vec<_>>();
+
+    let migrations = MigrationSource::<Pg>::migrations(&MIGRATIONS).map_err(|err| {
+        tracing::error!("cannot list migrations: {err}");
+        errors::server_error()
+    })?;
+
+    Ok(migrations
+        .iter()
+        .map(|migration| migration.name())
+        .filter(|name| !applied.contains(&name.version().to_string()))
+        .map(|name| name.to_string())
+        .collect())
+}
+
+/// Names of all the migrations, oldest first, along with whether they are
+/// applied
+pub async fn migration_status(config: &Config) -> color_eyre::Result<Vec<(String, bool)>> {
+    let mut conn = migration_connection(config).await?;
+
+    spawn_blocking(move || {
+        let applied = conn
+            .applied_migrations()
+            .map_err(|err| eyre!("cannot list applied migrations: {err}"))?;
+
+        let migrations = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+            .map_err(|err| eyre!("cannot list migrations: {err}"))?;
+
+        Ok(migrations
+            .iter()
+            .map(|migration| {
+                let name = migration.name();
+                (name.to_string(), applied.contains(&name.version()))
+            })
+            .collect())
+    })
+    .await?
+}
@@ -29,6 +29,10 @@ pub fn not_found() -> BoxedAppError {
     custom(StatusCode::NOT_FOUND)
 }
 
+pub fn unprocessable_entity() -> BoxedAppError {
+    custom(StatusCode::UNPROCESSABLE_ENTITY)
+}
+
 pub fn server_error() -> BoxedAppError {
     custom(StatusCode::INTERNAL_SERVER_ERROR)
 }
@@ -60,6 +64,13 @@ impl From<DieselError> for BoxedAppError {
         match err {
             DieselError::NotFound => not_found(),
             DieselError::DatabaseError(DatabaseErrorKind::ClosedConnection, _) => server_error(),
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                custom(StatusCode::CONFLICT)
+            }
+            DieselError::DatabaseError(
+                DatabaseErrorKind::ForeignKeyViolation | DatabaseErrorKind::CheckViolation,
+                _,
+            ) => unprocessable_entity(),
             _ => Box::new(err),
         }
     }
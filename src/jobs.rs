@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use color_eyre::eyre::Context;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::{db, supervisor::Worker};
+
+/// Number of minutes between two purges of the trash, unless configured
+pub const DEFAULT_TRASH_PURGE_INTERVAL_MINUTES: u32 = 60;
+
+/// Number of days deleted items stay in the trash, unless configured
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Permanently deletes the homeworks and subjects that have been in the
+/// trash for longer than `retention`
+pub async fn purge_trash(pool: &db::Pool, retention: chrono::Duration) -> color_eyre::Result<()> {
+    use crate::schema::homeworks;
+    use crate::schema::subjects;
+
+    let mut conn = pool.get().await.wrap_err("cannot get db connection")?;
+
+    let threshold = chrono::Utc::now() - retention;
+
+    let homeworks = diesel::delete(homeworks::table.filter(homeworks::deleted_at.lt(threshold)))
+        .execute(&mut conn)
+        .await
+        .wrap_err("cannot purge homeworks")?;
+
+    let subjects = diesel::delete(subjects::table.filter(subjects::deleted_at.lt(threshold)))
+        .execute(&mut conn)
+        .await
+        .wrap_err("cannot purge subjects")?;
+
+    if homeworks > 0 || subjects > 0 {
+        tracing::info!("purged {homeworks} homeworks and {subjects} subjects from the trash");
+    }
+
+    Ok(())
+}
+
+/// Purges the trash every `period`, until the worker is cancelled
+pub async fn run_trash_purge(
+    pool: db::Pool,
+    retention: chrono::Duration,
+    period: Duration,
+    worker: Worker,
+) {
+    let mut interval = tokio::time::interval(period);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = worker.cancelled() => break,
+        }
+
+        let started_at = chrono::Utc::now();
+        let result = purge_trash(&pool, retention).await;
+
+        if let Err(err) = &result {
+            tracing::error!("cannot purge trash: {err:?}");
+        }
+
+        worker.record_run(started_at, result.err().map(|err| format!("{err:#}")));
+    }
+}
@@ -1,21 +1,38 @@
+mod backup;
+mod cli;
 mod config;
 mod controllers;
 mod db;
 mod errors;
+mod jobs;
+mod metrics;
 mod models;
+mod quick_add;
 mod schema;
+mod supervisor;
+mod telemetry;
+mod timetable;
 mod utils;
 #[cfg(test)]
 mod tests;
 
 pub use config::Config;
 
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-
-use axum::{http::StatusCode, routing::get_service, Router};
+use axum::{
+    http::{HeaderValue, StatusCode},
+    middleware,
+    routing::get_service,
+    Router,
+};
+use clap::Parser;
 use color_eyre::eyre::Context;
-use tower_http::services::{ServeDir, ServeFile};
-use tracing_subscriber::EnvFilter;
+use config::Feature;
+use tower_http::{
+    cors::{AllowOrigin, Any, CorsLayer},
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    services::{ServeDir, ServeFile},
+    trace::TraceLayer,
+};
 use utoipa::OpenApi;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_swagger_ui::SwaggerUi;
@@ -23,70 +40,125 @@ use utoipa_swagger_ui::SwaggerUi;
 #[derive(Debug, Clone)]
 struct AppState {
     pool: db::Pool,
+    timezone: chrono_tz::Tz,
+    timetable_a_week: Option<chrono::NaiveDate>,
+    undo_window: chrono::Duration,
+    supervisor: supervisor::Supervisor,
+    started_at: chrono::DateTime<chrono::Utc>,
+    status_token: Option<String>,
 }
 
 #[derive(OpenApi)]
 #[openapi()]
 struct ApiDoc;
 
-pub async fn create_router(database_url: &str) -> color_eyre::Result<Router> {
-    let pool = db::create_database(database_url)
+pub async fn create_router(
+    config: &Config,
+    supervisor: &supervisor::Supervisor,
+) -> color_eyre::Result<Router> {
+    let pool = db::create_database(config)
         .await
         .wrap_err("cannot create db pool")?;
 
-    let state = AppState { pool };
+    if config.is_enabled(Feature::TrashPurge) {
+        let pool = pool.clone();
+        let retention = chrono::Duration::days(config.trash_retention_days.into());
+        let period =
+            std::time::Duration::from_secs(u64::from(config.trash_purge_interval_minutes) * 60);
+
+        supervisor.spawn("trash_purge", move |worker| {
+            jobs::run_trash_purge(pool.clone(), retention, period, worker)
+        });
+    }
+
+    let state = AppState {
+        pool,
+        timezone: config.timezone,
+        timetable_a_week: config.timetable_a_week,
+        undo_window: chrono::Duration::minutes(config.undo_window_minutes.into()),
+        supervisor: supervisor.clone(),
+        started_at: chrono::Utc::now(),
+        status_token: config.status_token.clone(),
+    };
 
     let handle_svc_error =
         |_| async move { (StatusCode::INTERNAL_SERVER_ERROR, "internal server error") };
 
-    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .nest("/api", controllers::router())
-        .nest_service(
-            "/assets",
-            get_service(ServeDir::new("./dist/assets")).handle_error(handle_svc_error),
-        )
-        .fallback_service(
-            get_service(ServeFile::new("./dist/index.html")).handle_error(handle_svc_error),
+    let mut router = OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .nest("/api", controllers::router(config))
+        .merge(controllers::probes_router());
+
+    if config.is_enabled(Feature::Metrics) {
+        router = router.merge(controllers::metrics_router());
+    }
+
+    if config.is_enabled(Feature::Frontend) {
+        router = router
+            .nest_service(
+                "/assets",
+                get_service(ServeDir::new(config.static_dir.join("assets")))
+                    .handle_error(handle_svc_error),
+            )
+            .fallback_service(
+                get_service(ServeFile::new(config.static_dir.join("index.html")))
+                    .handle_error(handle_svc_error),
+            );
+    }
+
+    let (mut router, api) = router.with_state(state).split_for_parts();
+
+    // Only once all the routes are added, for them to be tracked
+    router = router.route_layer(middleware::from_fn(metrics::track_requests));
+
+    if config.is_enabled(Feature::SwaggerUi) {
+        router = router.merge(SwaggerUi::new("/swagger-ui").url("/apidoc/openapi.json", api));
+    }
+
+    if !config.cors_origins.is_empty() {
+        router = router.layer(cors_layer(&config.cors_origins));
+    }
+
+    // Last added runs first: the request id is set before the span is made
+    router = router
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(telemetry::request_span)
+                .on_response(telemetry::record_response),
         )
-        .with_state(state)
-        .split_for_parts();
-
-    let router = router.merge(SwaggerUi::new("/swagger-ui").url("/apidoc/openapi.json", api));
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
 
     Ok(router)
 }
 
+/// Lets browsers call the API from `origins`, `*` allowing any origin
+fn cors_layer(origins: &[String]) -> CorsLayer {
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(Any)
+        .allow_headers(Any)
+}
+
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
     dotenvy::dotenv().ok();
 
-    let filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("homeworks=info"));
-    tracing_subscriber::fmt().with_env_filter(filter).init();
-
-    let config = Config::from_env()?;
+    let cli = cli::Cli::parse();
 
-    let sockaddr = SocketAddr::new(
-        config.addr.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
-        config.port.unwrap_or(8080),
-    );
+    let config = Config::load(cli.config.as_deref(), cli.settings)?;
 
-    db::run_migrations(&config.database_url)
-        .await
-        .wrap_err("cannot run migrations")?;
-
-    let router = create_router(&config.database_url).await?;
-
-    let listener = tokio::net::TcpListener::bind(sockaddr)
-        .await
-        .wrap_err("cannot bind socket address")?;
-
-    tracing::info!("listening on {sockaddr}");
-
-    axum::serve(listener, router)
-        .await
-        .wrap_err("cannot serve http")?;
+    let _telemetry = telemetry::init(&config)?;
 
-    Ok(())
+    cli::run(&config, cli.command.unwrap_or(cli::Command::Serve)).await
 }
@@ -0,0 +1,151 @@
+use std::{sync::LazyLock, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+/// Metrics of the app, exposed at `/metrics`
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    registry: Registry,
+
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+
+    pub db_queries: HistogramVec,
+    pub db_pool_connections: IntGauge,
+    pub db_pool_idle_connections: IntGauge,
+
+    pub job_runs: IntCounterVec,
+    pub job_duration: HistogramVec,
+
+    pub open_homeworks: IntGaugeVec,
+    pub overdue_homeworks: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("homeworks".to_owned()), None)
+            .expect("invalid metrics prefix");
+
+        let metrics = Self {
+            http_requests: IntCounterVec::new(
+                Opts::new("http_requests_total", "HTTP requests handled, by route"),
+                &["method", "path", "status"],
+            )
+            .expect("invalid metric"),
+            http_request_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "http_request_duration_seconds",
+                    "Time taken to handle HTTP requests, by route",
+                ),
+                &["method", "path"],
+            )
+            .expect("invalid metric"),
+            db_queries: HistogramVec::new(
+                HistogramOpts::new(
+                    "db_query_duration_seconds",
+                    "Time taken by database queries, by statement",
+                ),
+                &["operation", "outcome"],
+            )
+            .expect("invalid metric"),
+            db_pool_connections: IntGauge::new(
+                "db_pool_connections",
+                "Connections to the database open in the pool",
+            )
+            .expect("invalid metric"),
+            db_pool_idle_connections: IntGauge::new(
+                "db_pool_idle_connections",
+                "Connections to the database idle in the pool",
+            )
+            .expect("invalid metric"),
+            job_runs: IntCounterVec::new(
+                Opts::new("job_runs_total", "Runs of the background jobs"),
+                &["job", "outcome"],
+            )
+            .expect("invalid metric"),
+            job_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "job_duration_seconds",
+                    "Time taken by the runs of the background jobs",
+                ),
+                &["job"],
+            )
+            .expect("invalid metric"),
+            open_homeworks: IntGaugeVec::new(
+                Opts::new("open_homeworks", "Homeworks not done yet, by subject"),
+                &["subject"],
+            )
+            .expect("invalid metric"),
+            overdue_homeworks: IntGaugeVec::new(
+                Opts::new(
+                    "overdue_homeworks",
+                    "Homeworks not done past their due date, by subject",
+                ),
+                &["subject"],
+            )
+            .expect("invalid metric"),
+            registry,
+        };
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 9] = [
+            Box::new(metrics.http_requests.clone()),
+            Box::new(metrics.http_request_duration.clone()),
+            Box::new(metrics.db_queries.clone()),
+            Box::new(metrics.db_pool_connections.clone()),
+            Box::new(metrics.db_pool_idle_connections.clone()),
+            Box::new(metrics.job_runs.clone()),
+            Box::new(metrics.job_duration.clone()),
+            Box::new(metrics.open_homeworks.clone()),
+            Box::new(metrics.overdue_homeworks.clone()),
+        ];
+
+        for collector in collectors {
+            metrics
+                .registry
+                .register(collector)
+                .expect("metric registered twice");
+        }
+
+        metrics
+    }
+
+    /// Renders the metrics in the Prometheus text format
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        TextEncoder::new().encode_to_string(&self.registry.gather())
+    }
+}
+
+/// Counts and times the requests, labelled by the route they matched
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned());
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+
+    // Unmatched requests would give as many labels as paths tried
+    if let Some(path) = path {
+        METRICS
+            .http_requests
+            .with_label_values(&[&method, &path, response.status().as_str()])
+            .inc();
+
+        METRICS
+            .http_request_duration
+            .with_label_values(&[&method, &path])
+            .observe(start.elapsed().as_secs_f64());
+    }
+
+    response
+}
@@ -0,0 +1,64 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Subject;
+
+#[derive(
+    Debug,
+    Queryable,
+    Identifiable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[diesel(table_name = crate::schema::exams)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(belongs_to(Subject))]
+pub struct Exam {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub subject_id: Option<i32>,
+    pub title: String,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub duration_minutes: i32,
+    pub room: Option<String>,
+    /// Chapters covered by the exam
+    pub chapters: Vec<String>,
+}
+
+#[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::exams)]
+pub struct NewExam {
+    pub subject_id: Option<i32>,
+    pub title: String,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub duration_minutes: Option<i32>,
+    pub room: Option<String>,
+    pub chapters: Option<Vec<String>>,
+
+    /// Generates revision homeworks in the given number of days before the
+    /// exam, at most 60
+    #[diesel(skip_insertion)]
+    pub revision_days: Option<u32>,
+}
+
+#[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::exams)]
+pub struct UpdatedExam {
+    pub subject_id: Option<i32>,
+    pub title: Option<String>,
+    pub starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub duration_minutes: Option<i32>,
+    pub room: Option<String>,
+    pub chapters: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct RevisionPlan {
+    /// Number of days before the exam to schedule a revision on, 3 by default
+    /// and at most 60
+    pub days: Option<u32>,
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    Exam, Grade, Holiday, Homework, HomeworkStatusChange, HomeworkTag, HomeworkTemplate, Subject,
+    Tag, Term, TimeEntry, TimetableSlot,
+};
+
+/// Version of the layout of [`Export`], to be bumped whenever it changes
+pub const EXPORT_SCHEMA_VERSION: i32 = 1;
+
+/// Full dump of the data, trashed and archived records included
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Export {
+    pub schema_version: i32,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub subjects: Vec<Subject>,
+    pub tags: Vec<Tag>,
+    pub exams: Vec<Exam>,
+    pub homeworks: Vec<Homework>,
+    pub homework_tags: Vec<HomeworkTag>,
+    pub homework_status_changes: Vec<HomeworkStatusChange>,
+    pub homework_templates: Vec<HomeworkTemplate>,
+    pub grades: Vec<Grade>,
+    pub time_entries: Vec<TimeEntry>,
+    pub timetable_slots: Vec<TimetableSlot>,
+    pub terms: Vec<Term>,
+    pub holidays: Vec<Holiday>,
+}
+
+/// How an import deals with the existing data
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Adds the records of the dump, subjects and tags being matched with
+    /// existing ones by name
+    #[default]
+    Merge,
+    /// Deletes all the data before adding the records of the dump
+    Replace,
+}
+
+/// Number of records added by an import
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct ImportSummary {
+    pub subjects: usize,
+    pub tags: usize,
+    pub exams: usize,
+    pub homeworks: usize,
+    pub homework_templates: usize,
+    pub grades: usize,
+    pub time_entries: usize,
+    pub timetable_slots: usize,
+    pub terms: usize,
+    pub holidays: usize,
+}
+
+/// Row of the CSV export of homeworks
+#[derive(Debug, Serialize)]
+pub struct HomeworkCsvRow {
+    pub id: i32,
+    pub title: String,
+    pub description: String,
+    pub subject: Option<String>,
+    pub due_date: Option<chrono::DateTime<chrono::Utc>>,
+    pub status: crate::models::HomeworkStatus,
+    pub priority: crate::models::Priority,
+    pub estimated_minutes: Option<i32>,
+    pub difficulty: Option<i16>,
+    /// Names of the tags, separated by `;`
+    pub tags: String,
+    pub tracked_seconds: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Row of a CSV import, as it would be or was imported
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CsvImportRow {
+    /// Line of the row in the file, the header being the first one
+    pub line: u64,
+
+    pub title: String,
+
+    pub description: String,
+
+    pub due_date: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Name of the subject, which is created if it does not exist
+    pub subject: Option<String>,
+
+    /// Why the row cannot be imported
+    pub errors: Vec<String>,
+
+    /// The created homework, unless running dry
+    pub homework_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CsvImportResult {
+    pub rows: Vec<CsvImportRow>,
+
+    /// Subjects which do not exist yet
+    pub new_subjects: Vec<String>,
+}
@@ -0,0 +1,75 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Subject;
+
+/// Scale averages are expressed on, unless asked otherwise
+pub const DEFAULT_GRADE_SCALE: f64 = 20.0;
+
+#[derive(
+    Debug,
+    Queryable,
+    Identifiable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[diesel(table_name = crate::schema::grades)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(belongs_to(Subject))]
+pub struct Grade {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub subject_id: i32,
+    pub homework_id: Option<i32>,
+    pub title: String,
+    pub score: f64,
+    pub max_score: f64,
+    pub coefficient: f64,
+    pub graded_on: chrono::NaiveDate,
+}
+
+#[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::grades)]
+pub struct NewGrade {
+    pub homework_id: Option<i32>,
+    pub title: Option<String>,
+    pub score: f64,
+    pub max_score: Option<f64>,
+    pub coefficient: Option<f64>,
+    pub graded_on: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::grades)]
+pub struct UpdatedGrade {
+    pub subject_id: Option<i32>,
+    pub homework_id: Option<i32>,
+    pub title: Option<String>,
+    pub score: Option<f64>,
+    pub max_score: Option<f64>,
+    pub coefficient: Option<f64>,
+    pub graded_on: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SubjectAverage {
+    pub subject_id: i32,
+
+    /// Number of grades taken into account
+    pub grades: i64,
+
+    /// Weighted average, `null` without any grade
+    pub average: Option<f64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct GradeAverages {
+    /// Mean of the subject averages
+    pub overall: Option<f64>,
+
+    pub subjects: Vec<SubjectAverage>,
+}
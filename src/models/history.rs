@@ -0,0 +1,44 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Queryable, Identifiable, Selectable, Serialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::history_entries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub table_name: String,
+    pub record_id: i32,
+    pub action: HistoryAction,
+    /// Who made the change, as given by the `X-Actor` header
+    pub actor: Option<String>,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+    /// Before and after values of each changed field
+    #[schema(value_type = Object)]
+    pub changes: serde_json::Value,
+    /// Operation the change was part of
+    pub operation_id: Option<i32>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    diesel_derive_enum::DbEnum,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::HistoryAction"]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryAction {
+    Create,
+    Update,
+    /// Moved to the trash
+    Delete,
+    /// Restored from the trash
+    Restore,
+    /// Permanently deleted
+    Purge,
+}
@@ -0,0 +1,31 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Queryable, Identifiable, Selectable, Serialize, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::holidays)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Holiday {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub name: String,
+    pub starts_on: chrono::NaiveDate,
+    /// Last day of the holidays, inclusive
+    pub ends_on: chrono::NaiveDate,
+}
+
+#[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::holidays)]
+pub struct NewHoliday {
+    pub name: String,
+    pub starts_on: chrono::NaiveDate,
+    pub ends_on: chrono::NaiveDate,
+}
+
+#[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::holidays)]
+pub struct UpdatedHoliday {
+    pub name: Option<String>,
+    pub starts_on: Option<chrono::NaiveDate>,
+    pub ends_on: Option<chrono::NaiveDate>,
+}
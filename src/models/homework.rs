@@ -2,12 +2,22 @@ use crate::schema::homeworks;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::models::Subject;
+use crate::models::{Exam, Subject, Tag};
 
-#[derive(Debug, Queryable, Identifiable, Selectable, Associations, Serialize, utoipa::ToSchema)]
+#[derive(
+    Debug,
+    Queryable,
+    Identifiable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
 #[diesel(table_name = crate::schema::homeworks)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 #[diesel(belongs_to(Subject))]
+#[diesel(belongs_to(Exam))]
 pub struct Homework {
     pub id: i32,
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -17,6 +27,83 @@ pub struct Homework {
     pub description: String,
     pub done: bool,
     pub subject_id: Option<i32>,
+    pub priority: Priority,
+    pub estimated_minutes: Option<i32>,
+    pub difficulty: Option<i16>,
+    pub status: HomeworkStatus,
+    /// Exam this homework is a revision for
+    pub exam_id: Option<i32>,
+    /// When the homework was archived by a rollover
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the homework was moved to the trash
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Subject of the homework while that subject is in the trash
+    pub trashed_subject_id: Option<i32>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    diesel_derive_enum::DbEnum,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::Priority"]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+    Urgent,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    diesel_derive_enum::DbEnum,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::HomeworkStatus"]
+#[serde(rename_all = "lowercase")]
+pub enum HomeworkStatus {
+    Todo,
+    Started,
+    Done,
+    Submitted,
+    Graded,
+    Returned,
+}
+
+#[derive(
+    Debug,
+    Queryable,
+    Identifiable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[diesel(table_name = crate::schema::homework_status_changes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(belongs_to(Homework))]
+pub struct HomeworkStatusChange {
+    pub id: i32,
+    pub homework_id: i32,
+    pub from_status: Option<HomeworkStatus>,
+    pub to_status: HomeworkStatus,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
 }
 
 type HomeworkAllColumns = (
@@ -28,6 +115,14 @@ type HomeworkAllColumns = (
     homeworks::description,
     homeworks::done,
     homeworks::subject_id,
+    homeworks::priority,
+    homeworks::estimated_minutes,
+    homeworks::difficulty,
+    homeworks::status,
+    homeworks::exam_id,
+    homeworks::archived_at,
+    homeworks::deleted_at,
+    homeworks::trashed_subject_id,
 );
 
 pub const HOMEWORK_ALL_COLUMNS: HomeworkAllColumns = (
@@ -39,6 +134,14 @@ pub const HOMEWORK_ALL_COLUMNS: HomeworkAllColumns = (
     homeworks::description,
     homeworks::done,
     homeworks::subject_id,
+    homeworks::priority,
+    homeworks::estimated_minutes,
+    homeworks::difficulty,
+    homeworks::status,
+    homeworks::exam_id,
+    homeworks::archived_at,
+    homeworks::deleted_at,
+    homeworks::trashed_subject_id,
 );
 
 #[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
@@ -48,14 +151,123 @@ pub struct NewHomework {
     pub title: String,
     pub description: Option<String>,
     pub subject_id: Option<i32>,
+    pub priority: Option<Priority>,
+    pub status: Option<HomeworkStatus>,
+
+    /// Estimated effort, in minutes
+    pub estimated_minutes: Option<i32>,
+
+    /// Difficulty, from 1 to 5
+    pub difficulty: Option<i16>,
+
+    pub exam_id: Option<i32>,
+
+    /// Tags to assign to the homework
+    #[diesel(skip_insertion)]
+    pub tag_ids: Option<Vec<i32>>,
+
+    /// Resolves `due_date` on the server
+    #[diesel(skip_insertion)]
+    pub due: Option<Due>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Due {
+    /// Start of the next lesson of the subject, according to the timetable
+    NextLesson,
 }
 
 #[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
 #[diesel(table_name = crate::schema::homeworks)]
-pub struct UpdatedHomework {
+pub struct HomeworkChangeset {
     pub due_date: Option<chrono::DateTime<chrono::Utc>>,
     pub title: Option<String>,
     pub description: Option<String>,
     pub subject_id: Option<i32>,
+    pub status: Option<HomeworkStatus>,
+    pub priority: Option<Priority>,
+    pub estimated_minutes: Option<i32>,
+    pub difficulty: Option<i16>,
+    pub exam_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdatedHomework {
+    #[serde(flatten)]
+    pub changes: HomeworkChangeset,
+
+    /// Shorthand for setting the status to `done` or back to `todo`, ignored
+    /// if `status` is set
     pub done: Option<bool>,
+
+    /// Replaces the tags of the homework
+    pub tag_ids: Option<Vec<i32>>,
+}
+
+/// Change applied to every homework of a bulk request
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkOperation {
+    /// Sets the status to `done`, or back to `todo`
+    SetDone { done: bool },
+    /// Moves the homeworks to a subject, or out of any subject
+    MoveToSubject { subject_id: Option<i32> },
+    /// Shifts the due dates by a number of days, which may be negative
+    ShiftDueDate { days: i32 },
+    /// Moves the homeworks to the trash
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkStatus {
+    Updated,
+    /// The operation had nothing to change on the homework
+    Unchanged,
+    NotFound,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct BulkResult {
+    pub id: i32,
+
+    pub status: BulkStatus,
+
+    /// The homework after the operation, if it was updated
+    pub homework: Option<Homework>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct QuickAdd {
+    /// Free text such as `maths ex 3 p42 for friday 8am #group`
+    pub text: String,
+
+    /// Only parse the text, without creating the homework
+    pub dry_run: Option<bool>,
+}
+
+/// What was understood from a quick-add text
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct QuickAddPreview {
+    pub title: String,
+
+    pub subject: Option<Subject>,
+
+    pub due_date: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Existing tags mentioned with `#`
+    pub tags: Vec<Tag>,
+
+    /// Tags mentioned with `#` which do not exist yet, created along with the
+    /// homework
+    pub new_tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct QuickAddResult {
+    pub preview: QuickAddPreview,
+
+    /// The created homework, unless `dry_run` is set
+    pub homework: Option<Homework>,
 }
@@ -0,0 +1,84 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Priority, Subject};
+
+/// Recurring kind of homework
+///
+/// The title and description may contain `{date}` and `{chapter}`
+/// placeholders, filled when the template is instantiated.
+#[derive(
+    Debug,
+    Queryable,
+    Identifiable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[diesel(table_name = crate::schema::homework_templates)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(belongs_to(Subject))]
+pub struct HomeworkTemplate {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub subject_id: Option<i32>,
+    pub priority: Priority,
+    pub estimated_minutes: Option<i32>,
+    pub difficulty: Option<i16>,
+    pub tag_ids: Vec<i32>,
+    /// Homeworks are due this many days after the instantiation date, from 0
+    /// to 366
+    pub due_in_days: Option<i32>,
+    pub due_time: chrono::NaiveTime,
+}
+
+#[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::homework_templates)]
+pub struct NewHomeworkTemplate {
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub subject_id: Option<i32>,
+    pub priority: Option<Priority>,
+    pub estimated_minutes: Option<i32>,
+    pub difficulty: Option<i16>,
+    pub tag_ids: Option<Vec<i32>>,
+    pub due_in_days: Option<i32>,
+    pub due_time: Option<chrono::NaiveTime>,
+}
+
+#[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::homework_templates)]
+pub struct UpdatedHomeworkTemplate {
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub subject_id: Option<i32>,
+    pub priority: Option<Priority>,
+    pub estimated_minutes: Option<i32>,
+    pub difficulty: Option<i16>,
+    pub tag_ids: Option<Vec<i32>>,
+    pub due_in_days: Option<i32>,
+    pub due_time: Option<chrono::NaiveTime>,
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct TemplateInstantiation {
+    /// Fills `{date}` and is the start of `due_in_days`, today by default
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Fills `{chapter}`, required if the template uses it
+    pub chapter: Option<i32>,
+
+    /// Overrides the subject of the template
+    pub subject_id: Option<i32>,
+
+    /// Overrides the due date computed from the template
+    pub due_date: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -1,10 +1,34 @@
+mod exam;
+mod export;
+mod grade;
+mod history;
+mod holiday;
 mod homework;
+mod homework_template;
+mod operation;
+mod status;
 mod subject;
+mod tag;
+mod term;
+mod time_entry;
+mod timetable;
 
 use serde::Serialize;
 
+pub use self::exam::*;
+pub use self::export::*;
+pub use self::grade::*;
+pub use self::history::*;
+pub use self::holiday::*;
 pub use self::homework::*;
+pub use self::homework_template::*;
+pub use self::operation::*;
+pub use self::status::*;
 pub use self::subject::*;
+pub use self::tag::*;
+pub use self::term::*;
+pub use self::time_entry::*;
+pub use self::timetable::*;
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct HomeworkWithSubject {
@@ -12,6 +36,27 @@ pub struct HomeworkWithSubject {
     pub homework: Homework,
 
     pub subject: Option<Subject>,
+
+    pub tags: Vec<Tag>,
+
+    /// Total time tracked on the homework, including a running timer
+    pub tracked_seconds: i64,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ExamWithSubject {
+    #[serde(flatten)]
+    pub exam: Exam,
+
+    pub subject: Option<Subject>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TimetableSlotWithSubject {
+    #[serde(flatten)]
+    pub slot: TimetableSlot,
+
+    pub subject: Subject,
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -20,4 +65,14 @@ pub struct SubjectWithHomeworks {
     pub subject: Subject,
 
     pub homeworks: Vec<Homework>,
+
+    /// Current weighted average, on a scale of [`DEFAULT_GRADE_SCALE`]
+    pub average: Option<f64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Trash {
+    pub homeworks: Vec<Homework>,
+
+    pub subjects: Vec<Subject>,
 }
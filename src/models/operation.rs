@@ -0,0 +1,28 @@
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::models::{Homework, Subject};
+
+/// Changes made to homeworks and subjects by a single request
+#[derive(Debug, Queryable, Identifiable, Selectable, Serialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::operations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Operation {
+    pub id: i32,
+    pub performed_at: chrono::DateTime<chrono::Utc>,
+    pub actor: Option<String>,
+    /// Operation this one reverted
+    pub undo_of: Option<i32>,
+    pub undone_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct UndoneOperation {
+    pub operation: Operation,
+
+    /// Homeworks as they are after the undo
+    pub homeworks: Vec<Homework>,
+
+    /// Subjects as they are after the undo
+    pub subjects: Vec<Subject>,
+}
@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+/// Detailed state of the app, for operators
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Status {
+    pub version: String,
+
+    /// Commit the app was built from, when known
+    pub git_hash: Option<String>,
+
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub uptime_seconds: i64,
+
+    /// Names of the migrations not applied yet
+    pub pending_migrations: Vec<String>,
+
+    pub pool: PoolStatus,
+
+    pub workers: Vec<WorkerStatus>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PoolStatus {
+    /// Connections open, idle or in use
+    pub connections: u32,
+    pub idle_connections: u32,
+
+    /// Number of times a connection had to be waited for
+    pub waited: u64,
+
+    /// Number of times waiting for a connection timed out
+    pub timed_out: u64,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WorkerStatus {
+    pub name: String,
+    pub running: bool,
+
+    /// Number of times the worker was restarted after panicking
+    pub restarts: u32,
+
+    pub last_run: Option<JobRun>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct JobRun {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_ms: i64,
+
+    /// Why the run failed, if it did
+    pub error: Option<String>,
+}
+
+/// Outcome of each readiness check
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Readiness {
+    pub database: bool,
+    pub migrations: bool,
+    pub workers: bool,
+    pub shutting_down: bool,
+}
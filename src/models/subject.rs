@@ -1,7 +1,7 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Queryable, Identifiable, Selectable, Serialize, utoipa::ToSchema)]
+#[derive(Debug, Queryable, Identifiable, Selectable, Serialize, Deserialize, utoipa::ToSchema)]
 #[diesel(table_name = crate::schema::subjects)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Subject {
@@ -10,6 +10,11 @@ pub struct Subject {
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub name: String,
     pub hex_color: Option<String>,
+    /// When the subject was archived, archived subjects are hidden from
+    /// listings by default
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the subject was moved to the trash
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
@@ -21,7 +26,16 @@ pub struct NewSubject {
 
 #[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
 #[diesel(table_name = crate::schema::subjects)]
-pub struct UpdatedSubject {
+pub struct SubjectChangeset {
     pub name: Option<String>,
     pub hex_color: Option<String>,
 }
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdatedSubject {
+    #[serde(flatten)]
+    pub changes: SubjectChangeset,
+
+    /// Archives the subject, or brings it back
+    pub archived: Option<bool>,
+}
@@ -0,0 +1,50 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Homework;
+
+#[derive(Debug, Queryable, Identifiable, Selectable, Serialize, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::tags)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Tag {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub name: String,
+    pub hex_color: Option<String>,
+}
+
+#[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::tags)]
+pub struct NewTag {
+    pub name: String,
+    pub hex_color: Option<String>,
+}
+
+#[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::tags)]
+pub struct UpdatedTag {
+    pub name: Option<String>,
+    pub hex_color: Option<String>,
+}
+
+#[derive(
+    Debug,
+    Queryable,
+    Identifiable,
+    Selectable,
+    Associations,
+    Insertable,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[diesel(table_name = crate::schema::homework_tags)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(homework_id, tag_id))]
+#[diesel(belongs_to(Homework))]
+#[diesel(belongs_to(Tag))]
+pub struct HomeworkTag {
+    pub homework_id: i32,
+    pub tag_id: i32,
+}
@@ -0,0 +1,47 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Queryable, Identifiable, Selectable, Serialize, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::terms)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Term {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub name: String,
+    pub starts_on: chrono::NaiveDate,
+    /// Last day of the term, inclusive
+    pub ends_on: chrono::NaiveDate,
+}
+
+#[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::terms)]
+pub struct NewTerm {
+    pub name: String,
+    pub starts_on: chrono::NaiveDate,
+    pub ends_on: chrono::NaiveDate,
+}
+
+#[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::terms)]
+pub struct UpdatedTerm {
+    pub name: Option<String>,
+    pub starts_on: Option<chrono::NaiveDate>,
+    pub ends_on: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct Rollover {
+    /// First day of the new academic year, subjects created and homeworks
+    /// due before it are archived
+    pub starts_on: chrono::NaiveDate,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct RolloverSummary {
+    /// Number of subjects archived
+    pub subjects: usize,
+
+    /// Number of homeworks archived
+    pub homeworks: usize,
+}
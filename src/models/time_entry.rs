@@ -0,0 +1,71 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Homework;
+
+#[derive(
+    Debug,
+    Queryable,
+    Identifiable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[diesel(table_name = crate::schema::time_entries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(belongs_to(Homework))]
+pub struct TimeEntry {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub homework_id: i32,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// `null` while the timer is running
+    pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub note: String,
+}
+
+#[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::time_entries)]
+pub struct NewTimeEntry {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub ended_at: chrono::DateTime<chrono::Utc>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::time_entries)]
+pub struct UpdatedTimeEntry {
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, QueryableByName, Serialize, utoipa::ToSchema)]
+pub struct SubjectTimeStats {
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Int4>)]
+    pub subject_id: Option<i32>,
+
+    /// Number of homeworks worked on
+    #[diesel(sql_type = diesel::sql_types::Int8)]
+    pub homeworks: i64,
+
+    #[diesel(sql_type = diesel::sql_types::Int8)]
+    pub tracked_seconds: i64,
+
+    /// Sum of the estimates of the homeworks worked on
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Int8>)]
+    pub estimated_seconds: Option<i64>,
+}
+
+#[derive(Debug, QueryableByName, Serialize, utoipa::ToSchema)]
+pub struct WeekTimeStats {
+    /// First day of the week
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub week: chrono::NaiveDate,
+
+    #[diesel(sql_type = diesel::sql_types::Int8)]
+    pub tracked_seconds: i64,
+}
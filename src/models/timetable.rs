@@ -0,0 +1,76 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Subject;
+
+#[derive(
+    Debug,
+    Queryable,
+    Identifiable,
+    Selectable,
+    Associations,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[diesel(table_name = crate::schema::timetable_slots)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(belongs_to(Subject))]
+pub struct TimetableSlot {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub subject_id: i32,
+    /// ISO weekday, 1 is Monday
+    pub weekday: i16,
+    /// Local start time, in the server timezone
+    pub starts_at: chrono::NaiveTime,
+    /// Local end time, in the server timezone
+    pub ends_at: chrono::NaiveTime,
+    pub room: Option<String>,
+    pub rotation: WeekRotation,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    diesel_derive_enum::DbEnum,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::WeekRotation"]
+#[serde(rename_all = "lowercase")]
+pub enum WeekRotation {
+    /// Every week
+    Every,
+    /// "A" weeks only
+    A,
+    /// "B" weeks only
+    B,
+}
+
+#[derive(Debug, Insertable, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::timetable_slots)]
+pub struct NewTimetableSlot {
+    pub subject_id: i32,
+    pub weekday: i16,
+    pub starts_at: chrono::NaiveTime,
+    pub ends_at: chrono::NaiveTime,
+    pub room: Option<String>,
+    pub rotation: Option<WeekRotation>,
+}
+
+#[derive(Debug, AsChangeset, Deserialize, utoipa::ToSchema)]
+#[diesel(table_name = crate::schema::timetable_slots)]
+pub struct UpdatedTimetableSlot {
+    pub subject_id: Option<i32>,
+    pub weekday: Option<i16>,
+    pub starts_at: Option<chrono::NaiveTime>,
+    pub ends_at: Option<chrono::NaiveTime>,
+    pub room: Option<String>,
+    pub rotation: Option<WeekRotation>,
+}
@@ -0,0 +1,307 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+
+use crate::models::Subject;
+
+/// Words introducing a due date, dropped from the title along with it
+const CONNECTORS: &[&str] = &[
+    "for", "due", "by", "on", "at", "pour", "le", "pour le", "avant", "avant le", "a",
+];
+
+/// Time of the day homeworks are due at when neither the text nor the
+/// timetable tells
+pub const DEFAULT_DUE_TIME: NaiveTime = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+
+const WEEKDAYS: &[(&str, Weekday)] = &[
+    ("monday", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+    ("lundi", Weekday::Mon),
+    ("mardi", Weekday::Tue),
+    ("mercredi", Weekday::Wed),
+    ("jeudi", Weekday::Thu),
+    ("vendredi", Weekday::Fri),
+    ("samedi", Weekday::Sat),
+    ("dimanche", Weekday::Sun),
+];
+
+/// What could be understood from a quick-add text
+#[derive(Debug, Default)]
+pub struct Parsed {
+    pub title: String,
+    pub subject_id: Option<i32>,
+    pub due_on: Option<NaiveDate>,
+    pub due_at: Option<NaiveTime>,
+    pub tags: Vec<String>,
+}
+
+/// Splits `text` into a title, a subject among `subjects`, a due date
+/// relative to `today` and `#tags`
+pub fn parse(text: &str, subjects: &[Subject], today: NaiveDate) -> Parsed {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    let normalized = words.iter().map(|word| normalize(word)).collect::<Vec<_>>();
+
+    let mut parsed = Parsed::default();
+    let mut title = Vec::new();
+    let mut i = 0;
+
+    while i < words.len() {
+        if let Some(tag) = words[i].strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            parsed.tags.push(tag.to_string());
+            i += 1;
+            continue;
+        }
+
+        let rest = &normalized[i..];
+        let connector = CONNECTORS
+            .iter()
+            .map(|connector| connector.split(' ').count())
+            .filter(|len| {
+                *len < rest.len() && CONNECTORS.contains(&rest[..*len].join(" ").as_str())
+            })
+            .max()
+            .unwrap_or(0);
+
+        let matched = [connector, 0].into_iter().find_map(|skip| {
+            let rest = &rest[skip..];
+
+            if parsed.due_on.is_none() {
+                if let Some((date, len)) = parse_date(rest, today) {
+                    return Some((skip + len, Some(date), None));
+                }
+            }
+
+            if parsed.due_at.is_none() {
+                if let Some(time) = parse_time(&rest[0]) {
+                    return Some((skip + 1, None, Some(time)));
+                }
+            }
+
+            None
+        });
+
+        match matched {
+            Some((len, due_on, due_at)) => {
+                parsed.due_on = parsed.due_on.or(due_on);
+                parsed.due_at = parsed.due_at.or(due_at);
+                i += len;
+            }
+            None => {
+                title.push(i);
+                i += 1;
+            }
+        }
+    }
+
+    let mut subject_score = 0;
+    let mut subject_word = None;
+
+    for &index in &title {
+        for subject in subjects {
+            let score = similarity(&normalized[index], &normalize(&subject.name));
+
+            if score > subject_score {
+                subject_score = score;
+                subject_word = Some(index);
+                parsed.subject_id = Some(subject.id);
+            }
+        }
+    }
+
+    // Keep the subject as the title rather than leaving it empty
+    if title.len() == 1 {
+        subject_word = None;
+    }
+
+    parsed.title = title
+        .into_iter()
+        .filter(|index| Some(*index) != subject_word)
+        .map(|index| words[index])
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    parsed
+}
+
+/// Lowercases a word and strips its accents and punctuation
+fn normalize(word: &str) -> String {
+    word.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' => 'i',
+            'ô' | 'ö' => 'o',
+            'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            c => c,
+        })
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | ':' | '/'))
+        .collect()
+}
+
+/// How much a word looks like a subject name, 0 meaning not at all
+fn similarity(word: &str, name: &str) -> usize {
+    if word.chars().count() < 3 || name.is_empty() {
+        return 0;
+    }
+
+    if word == name {
+        return 4;
+    }
+
+    if name.starts_with(word) || word.starts_with(name) {
+        return 3;
+    }
+
+    let common_prefix = word
+        .chars()
+        .zip(name.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    if common_prefix >= 4 {
+        return 2;
+    }
+
+    if distance(word, name) <= word.chars().count() / 4 {
+        return 1;
+    }
+
+    0
+}
+
+/// Levenshtein distance between two words
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if a == *b {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Parses a date at the start of `words`, returning it with the number of
+/// words it spans
+fn parse_date(words: &[String], today: NaiveDate) -> Option<(NaiveDate, usize)> {
+    let word = |index: usize| words.get(index).map(String::as_str);
+
+    match (word(0)?, word(1), word(2)) {
+        ("today" | "aujourdhui", ..) => Some((today, 1)),
+        ("tomorrow" | "demain", ..) => Some((today + Duration::days(1), 1)),
+        ("apres-demain", ..) => Some((today + Duration::days(2), 1)),
+        ("day", Some("after"), Some("tomorrow")) => Some((today + Duration::days(2), 3)),
+        ("next", Some("week"), _) | ("semaine", Some("prochaine"), _) => {
+            Some((crate::timetable::week_start(today) + Duration::weeks(1), 2))
+        }
+        ("in" | "dans", Some(count), Some(unit)) => {
+            let count = count.parse::<i64>().ok()?;
+            let days = match unit {
+                "day" | "days" | "jour" | "jours" => count,
+                "week" | "weeks" | "semaine" | "semaines" => count.checked_mul(7)?,
+                _ => return None,
+            };
+
+            // Out of range counts are not dates, and stay in the title
+            Some((today.checked_add_signed(Duration::try_days(days)?)?, 3))
+        }
+        ("next", Some(weekday), _) => Some((next_weekday(today, parse_weekday(weekday)?), 2)),
+        (weekday, next, _) => {
+            if let Some(weekday) = parse_weekday(weekday) {
+                let len = if next == Some("prochain") { 2 } else { 1 };
+                return Some((next_weekday(today, weekday), len));
+            }
+
+            parse_numeric_date(weekday, today).map(|date| (date, 1))
+        }
+    }
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    WEEKDAYS
+        .iter()
+        .find(|(name, _)| *name == word)
+        .map(|(_, weekday)| *weekday)
+}
+
+/// First `weekday` strictly after `today`
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+
+    today + Duration::days(if days == 0 { 7 } else { days.into() })
+}
+
+/// Parses `2027-03-12`, `12/03/2027` or `12/03`, the latter being the next
+/// such date
+fn parse_numeric_date(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let parts = word
+        .split('/')
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match parts[..] {
+        [day, month, year] => NaiveDate::from_ymd_opt(year as i32, month, day),
+        [day, month] => {
+            let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+
+            if date < today {
+                NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+            } else {
+                Some(date)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Parses `8am`, `2:30pm`, `14:30`, `8h` or `8h30`
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    let (word, offset) = if let Some(word) = word.strip_suffix("am") {
+        (word, Some(0))
+    } else if let Some(word) = word.strip_suffix("pm") {
+        (word, Some(12))
+    } else {
+        (word, None)
+    };
+
+    let (hour, minute) = match word.split_once([':', 'h']) {
+        Some((hour, "")) => (hour, "0"),
+        Some((hour, minute)) => (hour, minute),
+        // A bare number is only a time with `am` or `pm`
+        None if offset.is_some() => (word, "0"),
+        None => return None,
+    };
+
+    let mut hour = hour.parse::<u32>().ok()?;
+    let minute = minute.parse::<u32>().ok()?;
+
+    if let Some(offset) = offset {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+
+        hour = hour % 12 + offset;
+    }
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
@@ -1,8 +1,140 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "history_action"))]
+    pub struct HistoryAction;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "homework_status"))]
+    pub struct HomeworkStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "priority"))]
+    pub struct Priority;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "week_rotation"))]
+    pub struct WeekRotation;
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+
+    exams (id) {
+        id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        subject_id -> Nullable<Int4>,
+        title -> Varchar,
+        starts_at -> Timestamptz,
+        duration_minutes -> Int4,
+        room -> Nullable<Varchar>,
+        chapters -> Array<Varchar>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+
+    grades (id) {
+        id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        subject_id -> Int4,
+        homework_id -> Nullable<Int4>,
+        title -> Varchar,
+        score -> Float8,
+        max_score -> Float8,
+        coefficient -> Float8,
+        graded_on -> Date,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+    use super::sql_types::HistoryAction;
+
+    history_entries (id) {
+        id -> Int8,
+        table_name -> Varchar,
+        record_id -> Int4,
+        action -> HistoryAction,
+        actor -> Nullable<Varchar>,
+        changed_at -> Timestamptz,
+        changes -> Jsonb,
+        operation_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+
+    holidays (id) {
+        id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        name -> Varchar,
+        starts_on -> Date,
+        ends_on -> Date,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+    use super::sql_types::Priority;
+
+    homework_templates (id) {
+        id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        name -> Varchar,
+        title -> Varchar,
+        description -> Varchar,
+        subject_id -> Nullable<Int4>,
+        priority -> Priority,
+        estimated_minutes -> Nullable<Int4>,
+        difficulty -> Nullable<Int2>,
+        tag_ids -> Array<Int4>,
+        due_in_days -> Nullable<Int4>,
+        due_time -> Time,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+    use super::sql_types::HomeworkStatus;
+
+    homework_status_changes (id) {
+        id -> Int4,
+        homework_id -> Int4,
+        from_status -> Nullable<HomeworkStatus>,
+        to_status -> HomeworkStatus,
+        changed_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+
+    homework_tags (homework_id, tag_id) {
+        homework_id -> Int4,
+        tag_id -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::*;
+    use super::sql_types::Priority;
+    use super::sql_types::HomeworkStatus;
 
     homeworks (id) {
         id -> Int4,
@@ -11,9 +143,30 @@ diesel::table! {
         due_date -> Nullable<Timestamptz>,
         title -> Varchar,
         description -> Varchar,
-        done -> Bool,
         textsearchable_index_col -> Tsvector,
         subject_id -> Nullable<Int4>,
+        priority -> Priority,
+        estimated_minutes -> Nullable<Int4>,
+        difficulty -> Nullable<Int2>,
+        status -> HomeworkStatus,
+        done -> Bool,
+        exam_id -> Nullable<Int4>,
+        archived_at -> Nullable<Timestamptz>,
+        deleted_at -> Nullable<Timestamptz>,
+        trashed_subject_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+
+    operations (id) {
+        id -> Int4,
+        performed_at -> Timestamptz,
+        actor -> Nullable<Varchar>,
+        undo_of -> Nullable<Int4>,
+        undone_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -27,12 +180,97 @@ diesel::table! {
         updated_at -> Timestamptz,
         name -> Varchar,
         hex_color -> Nullable<Varchar>,
+        archived_at -> Nullable<Timestamptz>,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+
+    tags (id) {
+        id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        name -> Varchar,
+        hex_color -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+
+    terms (id) {
+        id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        name -> Varchar,
+        starts_on -> Date,
+        ends_on -> Date,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+
+    time_entries (id) {
+        id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        homework_id -> Int4,
+        started_at -> Timestamptz,
+        ended_at -> Nullable<Timestamptz>,
+        note -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::*;
+    use super::sql_types::WeekRotation;
+
+    timetable_slots (id) {
+        id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        subject_id -> Int4,
+        weekday -> Int2,
+        starts_at -> Time,
+        ends_at -> Time,
+        room -> Nullable<Varchar>,
+        rotation -> WeekRotation,
     }
 }
 
+diesel::joinable!(exams -> subjects (subject_id));
+diesel::joinable!(grades -> homeworks (homework_id));
+diesel::joinable!(grades -> subjects (subject_id));
+diesel::joinable!(history_entries -> operations (operation_id));
+diesel::joinable!(homework_templates -> subjects (subject_id));
+diesel::joinable!(homework_status_changes -> homeworks (homework_id));
+diesel::joinable!(homework_tags -> homeworks (homework_id));
+diesel::joinable!(homework_tags -> tags (tag_id));
+diesel::joinable!(homeworks -> exams (exam_id));
 diesel::joinable!(homeworks -> subjects (subject_id));
+diesel::joinable!(time_entries -> homeworks (homework_id));
+diesel::joinable!(timetable_slots -> subjects (subject_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    exams,
+    grades,
+    history_entries,
+    holidays,
+    homework_templates,
+    homework_status_changes,
+    homework_tags,
     homeworks,
+    operations,
     subjects,
+    tags,
+    terms,
+    time_entries,
+    timetable_slots,
 );
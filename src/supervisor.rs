@@ -0,0 +1,203 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use futures_util::FutureExt;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use crate::{
+    metrics::METRICS,
+    models::{JobRun, WorkerStatus},
+};
+
+/// Time before a worker that panicked is started again
+const RESTART_DELAY: Duration = Duration::from_secs(10);
+
+/// Runs the background workers, restarting the ones that panic, and stops them
+/// on shutdown
+///
+/// Clones share the same workers and shutdown state.
+#[derive(Debug, Clone, Default)]
+pub struct Supervisor {
+    /// Cancelled once the app should not be sent more traffic
+    draining: CancellationToken,
+    /// Cancelled once the workers should stop
+    shutdown: CancellationToken,
+    tasks: TaskTracker,
+    statuses: Statuses,
+}
+
+type Statuses = Arc<Mutex<BTreeMap<&'static str, WorkerStatus>>>;
+
+/// Handle given to a worker, to know when to stop and to report its runs
+#[derive(Debug, Clone)]
+pub struct Worker {
+    name: &'static str,
+    shutdown: CancellationToken,
+    statuses: Statuses,
+}
+
+impl Worker {
+    /// Resolves once the worker should stop
+    pub async fn cancelled(&self) {
+        self.shutdown.cancelled().await
+    }
+
+    /// Records a run of the job of the worker, which began at `started_at`
+    /// and failed with `error` if any
+    pub fn record_run(&self, started_at: chrono::DateTime<chrono::Utc>, error: Option<String>) {
+        let duration = chrono::Utc::now() - started_at;
+        let outcome = if error.is_some() { "error" } else { "ok" };
+
+        METRICS
+            .job_runs
+            .with_label_values(&[self.name, outcome])
+            .inc();
+
+        METRICS
+            .job_duration
+            .with_label_values(&[self.name])
+            .observe(duration.num_milliseconds() as f64 / 1000.0);
+
+        self.update(|status| {
+            status.last_run = Some(JobRun {
+                started_at,
+                duration_ms: duration.num_milliseconds(),
+                error,
+            })
+        });
+    }
+
+    fn update(&self, update: impl FnOnce(&mut WorkerStatus)) {
+        let mut statuses = self.statuses.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(status) = statuses.get_mut(self.name) {
+            update(status);
+        }
+    }
+}
+
+impl Supervisor {
+    /// Runs the worker built by `worker`, which should return once its
+    /// [`Worker::cancelled`] resolves
+    pub fn spawn<F, Fut>(&self, name: &'static str, worker: F)
+    where
+        F: Fn(Worker) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handle = Worker {
+            name,
+            shutdown: self.shutdown.clone(),
+            statuses: self.statuses.clone(),
+        };
+
+        self.statuses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                name,
+                WorkerStatus {
+                    name: name.to_owned(),
+                    running: true,
+                    restarts: 0,
+                    last_run: None,
+                },
+            );
+
+        self.tasks.spawn(async move {
+            loop {
+                let result = AssertUnwindSafe(worker(handle.clone()))
+                    .catch_unwind()
+                    .await;
+
+                if result.is_ok() || handle.shutdown.is_cancelled() {
+                    break;
+                }
+
+                handle.update(|status| status.running = false);
+
+                tracing::error!("worker {name} panicked, restarting it in {RESTART_DELAY:?}");
+
+                tokio::select! {
+                    _ = tokio::time::sleep(RESTART_DELAY) => {}
+                    _ = handle.cancelled() => break,
+                }
+
+                handle.update(|status| {
+                    status.running = true;
+                    status.restarts += 1;
+                });
+            }
+
+            handle.update(|status| status.running = false);
+
+            tracing::debug!("worker {name} stopped");
+        });
+    }
+
+    /// State of every worker, by name
+    pub fn workers(&self) -> Vec<WorkerStatus> {
+        self.statuses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the app is shutting down, and should not be sent more traffic
+    pub fn is_shutting_down(&self) -> bool {
+        self.draining.is_cancelled()
+    }
+
+    /// Resolves once the shutdown has begun
+    pub async fn shutting_down(&self) {
+        self.draining.cancelled().await
+    }
+
+    /// Begins the shutdown, reporting the app as not ready while the workers
+    /// keep running until [`Self::shutdown`]
+    pub fn begin_shutdown(&self) {
+        self.draining.cancel();
+    }
+
+    /// Stops the workers, returning whether they all did within `timeout`
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        self.begin_shutdown();
+        self.shutdown.cancel();
+        self.tasks.close();
+
+        tokio::time::timeout(timeout, self.tasks.wait())
+            .await
+            .is_ok()
+    }
+}
+
+/// Resolves once SIGINT or, on unix, SIGTERM is received
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("cannot install SIGINT handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("cannot install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
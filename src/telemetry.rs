@@ -0,0 +1,202 @@
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Body,
+    extract::MatchedPath,
+    http::{HeaderMap, Request, Response},
+};
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use opentelemetry::{
+    propagation::{Extractor, TextMapPropagator},
+    trace::TracerProvider,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::{config::LogFormat, metrics::METRICS, Config};
+
+/// Name the traces are exported under
+const SERVICE_NAME: &str = "homeworks";
+
+/// Statements queries are labelled by, any other being labelled `other`
+const QUERY_OPERATIONS: &[&str] = &[
+    "select", "insert", "update", "delete", "with", "begin", "commit", "rollback", "set",
+];
+
+/// Flushes the traces not exported yet when dropped
+#[must_use]
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(err) = provider.shutdown() {
+                eprintln!("cannot export the last traces: {err}");
+            }
+        }
+    }
+}
+
+/// Logs to stdout in the configured format, and exports the traces to the
+/// configured OTLP collector if any
+pub fn init(config: &Config) -> color_eyre::Result<Telemetry> {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("homeworks=info"));
+
+    let fmt = match config.log_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    };
+
+    let provider = config
+        .otlp_endpoint
+        .as_deref()
+        .map(tracer_provider)
+        .transpose()?;
+
+    let otel = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt)
+        .with(otel)
+        .init();
+
+    Ok(Telemetry { provider })
+}
+
+/// Exports the traces in batches to the OTLP/HTTP collector at `endpoint`
+fn tracer_provider(endpoint: &str) -> color_eyre::Result<SdkTracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build())
+}
+
+/// Reads the trace context of a request from its headers
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+/// Span of a request, continuing the trace of the caller if it sent one
+pub fn request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    // Named after the route, paths giving as many names as ids
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+
+    let name = match route {
+        Some(route) => format!("{} {route}", request.method()),
+        None => request.method().to_string(),
+    };
+
+    let span = tracing::info_span!(
+        "request",
+        otel.name = name,
+        otel.kind = "server",
+        http.request.method = %request.method(),
+        http.route = route,
+        url.path = request.uri().path(),
+        http.response.status_code = tracing::field::Empty,
+        request_id,
+    );
+
+    span.set_parent(TraceContextPropagator::new().extract(&HeaderExtractor(request.headers())));
+
+    span
+}
+
+/// Records the status of the response of a request on its span
+pub fn record_response(response: &Response<Body>, _latency: Duration, span: &Span) {
+    span.record("http.response.status_code", response.status().as_u16());
+}
+
+/// Times the queries run on a database connection, and wraps them in spans
+#[derive(Debug, Default)]
+pub struct QueryInstrumentation {
+    query: Option<(Span, Instant)>,
+}
+
+impl Instrumentation for QueryInstrumentation {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { query, .. } => {
+                let query = query.to_string();
+                // Bound values may be personal data, the statement is enough
+                let statement = query
+                    .split_once(" -- binds: ")
+                    .map_or(query.as_str(), |(statement, _)| statement);
+
+                let span = tracing::info_span!(
+                    "db.query",
+                    otel.name = operation(statement),
+                    otel.kind = "client",
+                    db.system = "postgresql",
+                    db.statement = statement,
+                    error = tracing::field::Empty,
+                );
+
+                self.query = Some((span, Instant::now()));
+            }
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                let Some((span, started_at)) = self.query.take() else {
+                    return;
+                };
+
+                if let Some(error) = error {
+                    span.record("error", tracing::field::display(error));
+                }
+
+                let outcome = if error.is_some() { "error" } else { "ok" };
+
+                METRICS
+                    .db_queries
+                    .with_label_values(&[operation(&query.to_string()), outcome])
+                    .observe(started_at.elapsed().as_secs_f64());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Kind of statement `query` is, as one of [`QUERY_OPERATIONS`] or `other`
+fn operation(query: &str) -> &'static str {
+    let first = query.split_whitespace().next().unwrap_or_default();
+
+    QUERY_OPERATIONS
+        .iter()
+        .find(|operation| first.eq_ignore_ascii_case(operation))
+        .copied()
+        .unwrap_or("other")
+}
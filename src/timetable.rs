@@ -0,0 +1,111 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::models::{Holiday, TimetableSlot, WeekRotation};
+
+/// How far ahead to look for the next lesson
+const SEARCH_DAYS: i64 = 366;
+
+/// "A" week the weeks alternate from when none is configured, the first ISO
+/// week of 2024
+const DEFAULT_A_WEEK: NaiveDate = NaiveDate::from_ymd_opt(2024, 1, 1).expect("invalid date");
+
+/// Tells whether `date` falls in an "A" or a "B" week
+///
+/// Weeks alternate from the week of `a_week`, or from the first ISO week of
+/// 2024 when it is not set. Counting weeks rather than following the parity of
+/// ISO week numbers keeps alternating across 53-week years, as the iCalendar
+/// `INTERVAL=2` rules do.
+pub fn week_rotation(date: NaiveDate, a_week: Option<NaiveDate>) -> WeekRotation {
+    let a_week = a_week.unwrap_or(DEFAULT_A_WEEK);
+    let weeks = (week_start(date) - week_start(a_week)).num_weeks();
+
+    if weeks.rem_euclid(2) == 0 {
+        WeekRotation::A
+    } else {
+        WeekRotation::B
+    }
+}
+
+/// Tells whether a slot takes place on `date`
+pub fn occurs_on(slot: &TimetableSlot, date: NaiveDate, a_week: Option<NaiveDate>) -> bool {
+    if date.weekday().number_from_monday() != slot.weekday as u32 {
+        return false;
+    }
+
+    match slot.rotation {
+        WeekRotation::Every => true,
+        rotation => week_rotation(date, a_week) == rotation,
+    }
+}
+
+/// First date on or after `from` on which a slot takes place
+pub fn first_occurrence(
+    slot: &TimetableSlot,
+    from: NaiveDate,
+    a_week: Option<NaiveDate>,
+) -> Option<NaiveDate> {
+    (0..14)
+        .map(|days| from + Duration::days(days))
+        .find(|date| occurs_on(slot, *date, a_week))
+}
+
+/// Tells whether `date` falls during one of the `holidays`
+pub fn is_holiday(date: NaiveDate, holidays: &[Holiday]) -> bool {
+    holidays
+        .iter()
+        .any(|holiday| holiday.starts_on <= date && date <= holiday.ends_on)
+}
+
+/// Start of the first lesson among `slots` strictly after `after`, lessons
+/// during `holidays` being skipped
+pub fn next_lesson(
+    slots: &[TimetableSlot],
+    holidays: &[Holiday],
+    after: DateTime<Utc>,
+    timezone: Tz,
+    a_week: Option<NaiveDate>,
+) -> Option<DateTime<Utc>> {
+    let today = after.with_timezone(&timezone).date_naive();
+
+    (0..SEARCH_DAYS)
+        .map(|days| today + Duration::days(days))
+        .filter(|date| !is_holiday(*date, holidays))
+        .find_map(|date| {
+            slots
+                .iter()
+                .filter(|slot| occurs_on(slot, date, a_week))
+                .filter_map(|slot| localize(date.and_time(slot.starts_at), timezone))
+                .filter(|start| *start > after)
+                .min()
+        })
+}
+
+/// Converts a local date and time to UTC, picking the earliest instant on
+/// ambiguous times
+pub fn localize(date_time: chrono::NaiveDateTime, timezone: Tz) -> Option<DateTime<Utc>> {
+    timezone
+        .from_local_datetime(&date_time)
+        .earliest()
+        .map(|date_time| date_time.with_timezone(&Utc))
+}
+
+/// Instants between which the days from `starts_on` to `ends_on` (inclusive)
+/// take place, the end being exclusive
+pub fn date_range(
+    starts_on: NaiveDate,
+    ends_on: NaiveDate,
+    timezone: Tz,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let midnight = |date: NaiveDate| {
+        let date_time = date.and_time(chrono::NaiveTime::MIN);
+        localize(date_time, timezone).unwrap_or_else(|| date_time.and_utc())
+    };
+
+    (midnight(starts_on), midnight(ends_on + Duration::days(1)))
+}
+
+/// Monday of the week of `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday().into())
+}
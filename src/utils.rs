@@ -1,6 +1,8 @@
-use serde::{Deserialize, Deserializer};
+use serde::{de::IntoDeserializer, Deserialize, Deserializer};
 use std::str::FromStr;
 
+use crate::models::HomeworkStatus;
+
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct IdSequence(#[serde(deserialize_with = "from_querystring_seq")] Vec<u32>);
 
@@ -14,6 +16,21 @@ impl IdSequence {
     }
 }
 
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct StatusSequence(
+    #[serde(deserialize_with = "from_querystring_variants")] Vec<HomeworkStatus>,
+);
+
+impl StatusSequence {
+    pub fn statuses(self) -> Vec<HomeworkStatus> {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
 fn from_querystring_seq<'de, D>(deserializer: D) -> Result<Vec<u32>, D::Error>
 where
     D: Deserializer<'de>,
@@ -25,3 +42,16 @@ where
         .collect::<Result<Vec<_>, _>>()
         .map_err(serde::de::Error::custom)
 }
+
+fn from_querystring_variants<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let s = <String>::deserialize(deserializer)?;
+
+    s.split(',')
+        .map(|variant| T::deserialize(variant.into_deserializer()))
+        .collect::<Result<Vec<_>, serde::de::value::Error>>()
+        .map_err(serde::de::Error::custom)
+}